@@ -1,3 +1,8 @@
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
+#[cfg(all(feature = "allocator_api", feature = "rkyv"))]
+compile_error!("the allocator_api and rkyv features can't be used together, archived trees only come from the global allocator");
+
 mod tree;
 pub use crate::tree::*;
 
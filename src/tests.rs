@@ -1,6 +1,9 @@
 use super::*;
+#[cfg(feature = "bytebuffer")]
 use bytebuffer::*;
 use Position::*;
+use MergeDecision::*;
+use std::collections::HashSet;
 
 fn print_tree<T: std::fmt::Display>(tree: &Tree<T>){
     if let Some(root) = tree.get_root(){
@@ -297,8 +300,20 @@ fn change_root() {
         (FIRST_ROOT_CHILD_STR, 0),
         (LAST_ROOT_CHILD_STR, 0),
     ]));
+
+    //making the root the root again leaves the tree as it is.
+    tree.make_root(LAST_ROOT_CHILD_ID).unwrap();
+    assert_eq!(tree.len(), 4);
+    assert_eq!(tree.get_root(), Some(LAST_ROOT_CHILD_ID));
+    assert_eq!(tree.depth_of(LAST_ROOT_CHILD_ID).unwrap(), 0);
+
+    let mut tree = Tree::new_with_root(0);
+    let child = tree.new_node(1, LastChild, ROOT_ID).unwrap();
+    tree.make_root(ROOT_ID).unwrap();
+    assert_eq!(tree.children_of(ROOT_ID).unwrap(), vec![child]);
 }
 
+#[cfg(feature = "bytebuffer")]
 #[test]
 fn bytes(){
     let mut tree = Tree::new();
@@ -344,4 +359,2433 @@ fn bytes(){
         (23, 0),
         (30, 0),
     ]));
-}
\ No newline at end of file
+}
+#[test]
+fn new_nodes() {
+    let mut tree = make_tree();
+
+    let new1 = "new1";
+    let new2 = "new2";
+    let new3 = "new3";
+
+    let ids = tree.new_nodes(vec![new1, new2], FirstChild, FIRST_ROOT_CHILD_ID).unwrap();
+    assert_eq!(ids.len(), 2);
+    assert_eq!(tree.first_child_of(FIRST_ROOT_CHILD_ID).unwrap(), Some(ids[0]));
+    assert_eq!(tree.last_child_of(FIRST_ROOT_CHILD_ID).unwrap(), Some(ids[1]));
+
+    tree.new_nodes(vec![new1, new2, new3], SiblingAfter, FIRST_ROOT_CHILD_ID).unwrap();
+
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 5),
+        (FIRST_ROOT_CHILD_STR, 2),
+        (new1, 0),
+        (new2, 0),
+        (new1, 0),
+        (new2, 0),
+        (new3, 0),
+        (LAST_ROOT_CHILD_STR, 0),
+    ]));
+
+    assert!(tree.new_nodes(vec![new1], SiblingBefore, ROOT_ID).is_err());
+    assert!(tree.new_nodes(Vec::new(), LastChild, ROOT_ID).unwrap().is_empty());
+}
+
+#[test]
+fn replace_subtree() {
+    let mut tree = make_tree();
+    tree.new_node("removed child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    let mut other = Tree::new_with_root("new");
+    other.new_node("new child", LastChild, 0).unwrap();
+
+    let old = tree.replace_subtree(LAST_ROOT_CHILD_ID, other).unwrap();
+
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 2),
+        (FIRST_ROOT_CHILD_STR, 0),
+        ("new", 1),
+        ("new child", 0),
+    ]));
+
+    assert!(tree_matches(&old, vec![
+        (LAST_ROOT_CHILD_STR, 1),
+        ("removed child", 0),
+    ]));
+
+    let old = tree.replace_subtree(ROOT_ID, Tree::new()).unwrap();
+
+    assert!(tree_matches(&tree, vec![]));
+    assert_eq!(tree.get_root(), None);
+    assert_eq!(old.len(), 4);
+}
+
+#[test]
+fn extract_subtrees() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    assert!(tree.extract_subtrees(&[LAST_ROOT_CHILD_ID, child]).is_err());
+    assert!(tree.extract_subtrees(&[FIRST_ROOT_CHILD_ID, FIRST_ROOT_CHILD_ID]).is_err());
+    assert_eq!(tree.len(), 4);
+
+    let trees = tree.extract_subtrees(&[LAST_ROOT_CHILD_ID, FIRST_ROOT_CHILD_ID]).unwrap();
+
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 0),
+    ]));
+
+    assert!(tree_matches(&trees[0], vec![
+        (LAST_ROOT_CHILD_STR, 1),
+        ("child", 0),
+    ]));
+
+    assert!(tree_matches(&trees[1], vec![
+        (FIRST_ROOT_CHILD_STR, 0),
+    ]));
+}
+
+#[test]
+fn forest() {
+    let mut forest = Forest::from(make_tree());
+    let first = forest.roots()[0];
+    let first_children = forest.children_of(first).unwrap();
+    let second = forest.add_root("second root");
+    let third = forest.add_tree(make_tree()).unwrap();
+
+    assert_eq!(forest.len(), 7);
+    assert_eq!(forest.roots(), vec![first, second, third]);
+    assert_eq!(forest.parent_of(first).unwrap(), None);
+    assert_eq!(forest.parent_of(first_children[0]).unwrap(), Some(first));
+
+    let before = forest.new_node("before", SiblingBefore, first).unwrap();
+    forest.move_to(first_children[1], SiblingAfter, third).unwrap();
+
+    assert_eq!(forest.roots(), vec![before, first, second, third, first_children[1]]);
+
+    #[cfg(feature = "bytebuffer")]
+    {
+        let new_forest = Forest::<i32>::from_trees(vec![Tree::new_with_root(0), Tree::new_with_root(1)]);
+        let bytes: Vec<u8> = new_forest.into_bytes().collect();
+        let new_forest = Forest::<i32>::from_bytes(&mut bytes.into_iter()).unwrap();
+        let roots = new_forest.roots();
+
+        assert_eq!(roots.len(), 2);
+        assert_eq!(new_forest.data_at(roots[1]).unwrap(), &1);
+    }
+
+    let tree = forest.remove_tree(third).unwrap();
+
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 2),
+        (FIRST_ROOT_CHILD_STR, 0),
+        (LAST_ROOT_CHILD_STR, 0),
+    ]));
+    assert_eq!(forest.roots(), vec![before, first, second, first_children[1]]);
+
+    assert!(!forest.is_empty());
+    assert!(Forest::<i32>::new().is_empty());
+    let child = forest.new_node("child", LastChild, first_children[0]).unwrap();
+    assert_eq!(forest.path_to_root(child).unwrap(), vec![child, first_children[0], first]);
+    assert_eq!(forest.path_between(child, first).unwrap(), vec![child, first_children[0], first]);
+    assert!(matches!(forest.path_between(child, second), Err(TreeErr::InvalidId)));
+    assert_eq!(forest.height_of(first).unwrap(), 2);
+    assert_eq!(forest.nth_child(first, 0).unwrap(), Some(first_children[0]));
+    assert_eq!(forest.sibling_index(second).unwrap(), 2);
+    assert_eq!(forest.find(first, |data| *data == "child").unwrap(), Some(child));
+    assert_eq!(forest.find_all(first, |data| data.contains("child")).unwrap(), vec![first_children[0], child]);
+
+    assert_eq!(forest.next_node(child).unwrap(), Some(second));
+    assert_eq!(forest.next_node(first_children[1]).unwrap(), None);
+    assert_eq!(forest.prev_node(first).unwrap(), Some(before));
+    assert_eq!(forest.prev_node(before).unwrap(), None);
+    assert_eq!(forest.range(first_children[0], second).unwrap(), vec![first_children[0], child, second]);
+    assert_eq!(forest.range(second, first).unwrap(), Vec::<usize>::new());
+    assert!(forest.is_root(second).unwrap());
+    assert!(!forest.is_root(child).unwrap());
+    assert!(forest.is_leaf(second).unwrap());
+    assert!(!forest.is_leaf(first).unwrap());
+    assert!(forest.is_ancestor_of(first, child).unwrap());
+    assert!(forest.is_descendant_of(child, first).unwrap());
+    assert!(!forest.is_ancestor_of(second, child).unwrap());
+    assert!(matches!(forest.is_root(0), Err(TreeErr::InvalidId)));
+
+    let same = Forest::from_trees(vec![make_tree(), Tree::new_with_root("second root")]);
+    let mut other = Forest::from(make_tree());
+    assert_ne!(same, other);
+    other.add_root("second root");
+    assert_eq!(same, other);
+    assert_ne!(same, Forest::from_trees(vec![Tree::new_with_root("second root"), make_tree()]));
+    assert_eq!(format!("{:?}", other), format!("{:?}", [(ROOT_STR, 2), (FIRST_ROOT_CHILD_STR, 0), (LAST_ROOT_CHILD_STR, 0), ("second root", 0)]));
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_string(&forest).unwrap();
+        let read: Forest<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.len(), forest.len());
+        assert_eq!(read.roots().len(), 4);
+        assert_eq!(read.data_at(read.roots()[2]).unwrap(), "second root");
+        assert_eq!(serde_json::to_string(&read).unwrap(), json);
+    }
+}
+
+#[test]
+fn merge_by_key() {
+    let mut tree = Tree::new_with_root(("root", 0));
+    let docs = tree.new_node(("docs", 0), LastChild, 0).unwrap();
+    tree.new_node(("a.txt", 0), LastChild, docs).unwrap();
+    tree.new_node(("src", 0), LastChild, 0).unwrap();
+
+    let mut other = Tree::new_with_root(("root", 1));
+    let other_docs = other.new_node(("docs", 1), LastChild, 0).unwrap();
+    other.new_node(("a.txt", 1), LastChild, other_docs).unwrap();
+    other.new_node(("b.txt", 1), LastChild, other_docs).unwrap();
+    let other_bin = other.new_node(("bin", 1), LastChild, 0).unwrap();
+    other.new_node(("run", 1), LastChild, other_bin).unwrap();
+
+    tree.merge_by_key(other, |data| data.0);
+
+    let expected = [
+        (("root", 0), 3),
+        (("docs", 0), 2),
+        (("a.txt", 0), 0),
+        (("b.txt", 1), 0),
+        (("src", 0), 0),
+        (("bin", 1), 1),
+        (("run", 1), 0),
+    ];
+
+    assert_eq!(tree.len(), expected.len());
+
+    for (node, expected) in tree.sub_tree_info(0).unwrap().iter().zip(expected.iter()) {
+        assert_eq!(tree.data_at(node.id).unwrap(), &expected.0);
+        assert_eq!(node.child_count, expected.1);
+    }
+}
+
+#[test]
+fn merge_by_key_with() {
+    let mut tree = Tree::new_with_root(("root", 1));
+    let keep = tree.new_node(("keep", 1), LastChild, 0).unwrap();
+    tree.new_node(("old", 1), LastChild, keep).unwrap();
+    let replace = tree.new_node(("replace", 1), LastChild, 0).unwrap();
+    tree.new_node(("old", 1), LastChild, replace).unwrap();
+
+    let mut other = Tree::new_with_root(("root", 2));
+    let other_keep = other.new_node(("keep", 2), LastChild, 0).unwrap();
+    other.new_node(("new", 2), LastChild, other_keep).unwrap();
+    let other_replace = other.new_node(("replace", 2), LastChild, 0).unwrap();
+    other.new_node(("new", 2), LastChild, other_replace).unwrap();
+
+    tree.merge_by_key_with(other, |data| data.0, |data, other| {
+        match data.0 {
+            "keep"    => Keep,
+            "replace" => { *data = other; Replace },
+            _         => { data.1 += other.1; Combine },
+        }
+    });
+
+    let expected = [
+        (("root", 3), 2),
+        (("keep", 1), 1),
+        (("old", 1), 0),
+        (("replace", 2), 1),
+        (("new", 2), 0),
+    ];
+
+    assert_eq!(tree.len(), expected.len());
+
+    for (node, expected) in tree.sub_tree_info(0).unwrap().iter().zip(expected.iter()) {
+        assert_eq!(tree.data_at(node.id).unwrap(), &expected.0);
+        assert_eq!(node.child_count, expected.1);
+    }
+}
+
+#[test]
+fn group_children() {
+    let mut tree = make_tree();
+    tree.new_node("3rd root child", LastChild, ROOT_ID).unwrap();
+
+    let group = tree.group_children(ROOT_ID, 1..3, "group").unwrap();
+
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 2),
+        (FIRST_ROOT_CHILD_STR, 0),
+        ("group", 2),
+        (LAST_ROOT_CHILD_STR, 0),
+        ("3rd root child", 0),
+    ]));
+
+    tree.group_children(group, 2..2, "empty").unwrap();
+
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 2),
+        (FIRST_ROOT_CHILD_STR, 0),
+        ("group", 3),
+        (LAST_ROOT_CHILD_STR, 0),
+        ("3rd root child", 0),
+        ("empty", 0),
+    ]));
+
+    assert!(tree.group_children(ROOT_ID, 1..3, "too far").is_err());
+    assert_eq!(tree.len(), 6);
+}
+
+#[test]
+fn promote() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+    tree.new_node("sibling", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    tree.promote(child).unwrap();
+
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 2),
+        ("child", 1),
+        (FIRST_ROOT_CHILD_STR, 1),
+        ("sibling", 0),
+        (LAST_ROOT_CHILD_STR, 0),
+    ]));
+
+    tree.promote(LAST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.get_root(), Some(LAST_ROOT_CHILD_ID));
+    assert_eq!(tree.parent_of(LAST_ROOT_CHILD_ID).unwrap(), None);
+    assert!(tree_matches(&tree, vec![
+        (LAST_ROOT_CHILD_STR, 1),
+        (ROOT_STR, 1),
+        ("child", 1),
+        (FIRST_ROOT_CHILD_STR, 1),
+        ("sibling", 0),
+    ]));
+
+    assert!(tree.promote(LAST_ROOT_CHILD_ID).is_err());
+}
+
+#[test]
+fn reroot() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+    tree.new_node("grandchild", LastChild, child).unwrap();
+
+    tree.reroot(child).unwrap();
+
+    assert_eq!(tree.get_root(), Some(child));
+    assert_eq!(tree.parent_of(child).unwrap(), None);
+    assert!(tree_matches(&tree, vec![
+        ("child", 2),
+        ("grandchild", 0),
+        (LAST_ROOT_CHILD_STR, 1),
+        (ROOT_STR, 1),
+        (FIRST_ROOT_CHILD_STR, 0),
+    ]));
+    assert_eq!(tree.depth_of(ROOT_ID).unwrap(), 2);
+    assert_eq!(tree.depth_of(FIRST_ROOT_CHILD_ID).unwrap(), 3);
+    #[cfg(feature = "subtree_sizes")]
+    assert_eq!(tree.subtree_size(LAST_ROOT_CHILD_ID).unwrap(), 3);
+
+    //flipping a long chain fixes the sizes and depths once instead of at every step.
+    const DEPTH: usize = 5_000;
+    let mut tree = Tree::new_with_root(0);
+    let mut parent = ROOT_ID;
+    for i in 1..DEPTH {
+        tree.new_node(i, FirstChild, parent).unwrap();
+        parent = tree.new_node(i, LastChild, parent).unwrap();
+    }
+    tree.reroot(parent).unwrap();
+    assert_eq!(tree.depth_of(ROOT_ID).unwrap(), DEPTH - 1);
+    assert_eq!(tree.height_of(parent).unwrap(), DEPTH);
+    #[cfg(feature = "subtree_sizes")]
+    assert_eq!(tree.subtree_size(ROOT_ID).unwrap(), 2);
+}
+
+#[test]
+fn canonicalize_by() {
+    let mut tree = Tree::new_with_root(0);
+    tree.new_node(3, LastChild, 0).unwrap();
+    let one = tree.new_node(1, LastChild, 0).unwrap();
+    tree.new_node(12, LastChild, one).unwrap();
+    tree.new_node(11, LastChild, one).unwrap();
+    tree.new_node(2, LastChild, 0).unwrap();
+
+    tree.canonicalize_by(|data| *data);
+
+    assert!(tree_matches(&tree, vec![
+        (0, 3),
+        (1, 2),
+        (11, 0),
+        (12, 0),
+        (2, 0),
+        (3, 0),
+    ]));
+    assert_eq!(tree.last_child_of(one).unwrap().map(|id| *tree.data_at(id).unwrap()), Some(12));
+}
+
+#[test]
+fn flatten() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+    tree.new_node("grandchild", LastChild, child).unwrap();
+
+    tree.flatten(ROOT_ID).unwrap();
+
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 4),
+        (FIRST_ROOT_CHILD_STR, 0),
+        ("child", 0),
+        ("grandchild", 0),
+        (LAST_ROOT_CHILD_STR, 0),
+    ]));
+    assert_eq!(tree.parent_of(child).unwrap(), Some(ROOT_ID));
+}
+
+#[test]
+fn find() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.find(ROOT_ID, |data| data.ends_with("child")).unwrap(), Some(FIRST_ROOT_CHILD_ID));
+    assert_eq!(tree.find(ROOT_ID, |data| *data == "child").unwrap(), Some(child));
+    assert_eq!(tree.find(ROOT_ID, |data| *data == LAST_ROOT_CHILD_STR).unwrap(), Some(LAST_ROOT_CHILD_ID));
+    assert_eq!(tree.find(FIRST_ROOT_CHILD_ID, |data| *data == LAST_ROOT_CHILD_STR).unwrap(), None);
+    assert!(tree.find(10, |_| true).is_err());
+}
+
+#[test]
+fn find_all() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.find_all(ROOT_ID, |data| data.ends_with("child")).unwrap(), vec![FIRST_ROOT_CHILD_ID, child, LAST_ROOT_CHILD_ID]);
+    assert_eq!(tree.find_all(FIRST_ROOT_CHILD_ID, |data| data.ends_with("child")).unwrap(), vec![FIRST_ROOT_CHILD_ID, child]);
+    assert!(tree.find_all(ROOT_ID, |_| false).unwrap().is_empty());
+}
+
+#[test]
+fn contains() {
+    let mut tree = make_tree();
+
+    assert!(tree.contains(ROOT_ID));
+    assert!(tree.contains(LAST_ROOT_CHILD_ID));
+    assert!(!tree.contains(3));
+
+    tree.remove(LAST_ROOT_CHILD_ID).unwrap();
+    assert!(!tree.contains(LAST_ROOT_CHILD_ID));
+
+    assert!(!Forest::<i32>::new().contains(0));
+}
+
+#[test]
+fn height_of() {
+    let mut tree = make_tree();
+
+    assert_eq!(tree.height_of(ROOT_ID).unwrap(), 1);
+    assert_eq!(tree.height_of(FIRST_ROOT_CHILD_ID).unwrap(), 0);
+
+    let child = tree.new_node("child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+    tree.new_node("grandchild", LastChild, child).unwrap();
+
+    assert_eq!(tree.height_of(ROOT_ID).unwrap(), 3);
+    assert_eq!(tree.height_of(LAST_ROOT_CHILD_ID).unwrap(), 2);
+}
+
+#[test]
+fn relationships() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    assert!(tree.is_ancestor_of(ROOT_ID, child).unwrap());
+    assert!(tree.is_ancestor_of(LAST_ROOT_CHILD_ID, child).unwrap());
+    assert!(!tree.is_ancestor_of(FIRST_ROOT_CHILD_ID, child).unwrap());
+    assert!(!tree.is_ancestor_of(child, child).unwrap());
+    assert!(tree.is_descendant_of(child, ROOT_ID).unwrap());
+    assert!(!tree.is_descendant_of(ROOT_ID, child).unwrap());
+
+    assert!(tree.is_leaf(child).unwrap());
+    assert!(!tree.is_leaf(LAST_ROOT_CHILD_ID).unwrap());
+    assert!(tree.is_root(ROOT_ID).unwrap());
+    assert!(!tree.is_root(child).unwrap());
+    assert!(tree.is_root(10).is_err());
+}
+
+#[test]
+fn path_to_root() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.path_to_root(child).unwrap(), vec![child, LAST_ROOT_CHILD_ID, ROOT_ID]);
+    assert_eq!(tree.path_to_root(ROOT_ID).unwrap(), vec![ROOT_ID]);
+}
+
+#[test]
+fn path_between() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.path_between(FIRST_ROOT_CHILD_ID, child).unwrap(), vec![FIRST_ROOT_CHILD_ID, ROOT_ID, LAST_ROOT_CHILD_ID, child]);
+    assert_eq!(tree.path_between(child, ROOT_ID).unwrap(), vec![child, LAST_ROOT_CHILD_ID, ROOT_ID]);
+    assert_eq!(tree.path_between(child, child).unwrap(), vec![child]);
+
+    assert_eq!(tree.distance(FIRST_ROOT_CHILD_ID, child).unwrap(), 3);
+    assert_eq!(tree.distance(ROOT_ID, child).unwrap(), 2);
+    assert_eq!(tree.distance(child, child).unwrap(), 0);
+}
+
+#[test]
+fn sibling_index() {
+    let mut tree = make_tree();
+    let before = tree.new_node("before", FirstChild, ROOT_ID).unwrap();
+
+    assert_eq!(tree.sibling_index(before).unwrap(), 0);
+    assert_eq!(tree.sibling_index(FIRST_ROOT_CHILD_ID).unwrap(), 1);
+    assert_eq!(tree.sibling_index(LAST_ROOT_CHILD_ID).unwrap(), 2);
+    assert_eq!(tree.sibling_index(ROOT_ID).unwrap(), 0);
+}
+
+#[test]
+fn nth_child() {
+    let tree = make_tree();
+
+    assert_eq!(tree.nth_child(ROOT_ID, 0).unwrap(), Some(FIRST_ROOT_CHILD_ID));
+    assert_eq!(tree.nth_child(ROOT_ID, 1).unwrap(), Some(LAST_ROOT_CHILD_ID));
+    assert_eq!(tree.nth_child(ROOT_ID, 2).unwrap(), None);
+    assert_eq!(tree.nth_child(FIRST_ROOT_CHILD_ID, 0).unwrap(), None);
+}
+
+#[test]
+fn index_paths() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.path_of(child).unwrap(), vec![1, 0]);
+    assert_eq!(tree.path_of(ROOT_ID).unwrap(), Vec::<usize>::new());
+
+    assert_eq!(tree.node_at_path(&[1, 0]).unwrap(), child);
+    assert_eq!(tree.node_at_path(&[]).unwrap(), ROOT_ID);
+    assert_eq!(tree.node_at_path(&[0]).unwrap(), FIRST_ROOT_CHILD_ID);
+    assert!(tree.node_at_path(&[2]).is_err());
+    assert!(Tree::<i32>::new().node_at_path(&[]).is_err());
+}
+
+#[test]
+fn subtree_size() {
+    fn sizes_match<T>(tree: &Tree<T>){
+        for id in tree.sub_tree(tree.get_root().unwrap()).unwrap(){
+            assert_eq!(tree.subtree_size(id).unwrap(), tree.sub_tree(id).unwrap().len());
+        }
+    }
+
+    let mut tree = make_tree();
+
+    assert_eq!(tree.subtree_size(ROOT_ID).unwrap(), 3);
+    assert_eq!(tree.subtree_size(FIRST_ROOT_CHILD_ID).unwrap(), 1);
+
+    let clone = tree.clone_to(ROOT_ID, LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.subtree_size(ROOT_ID).unwrap(), 6);
+    assert_eq!(tree.subtree_size(FIRST_ROOT_CHILD_ID).unwrap(), 4);
+    assert_eq!(tree.subtree_size(clone).unwrap(), 3);
+    sizes_match(&tree);
+
+    tree.move_to(clone, SiblingAfter, LAST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.subtree_size(ROOT_ID).unwrap(), 6);
+    assert_eq!(tree.subtree_size(FIRST_ROOT_CHILD_ID).unwrap(), 1);
+    sizes_match(&tree);
+
+    tree.remove(clone).unwrap();
+
+    assert_eq!(tree.subtree_size(ROOT_ID).unwrap(), 3);
+    sizes_match(&tree);
+}
+
+#[test]
+fn stats() {
+    let mut tree = make_tree();
+    tree.new_nodes(vec!["a", "b", "c"], LastChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.stats(), TreeStats {
+        node_count: 6,
+        height: 2,
+        leaf_count: 4,
+        max_children: 3,
+        level_counts: vec![1, 2, 3],
+    });
+
+    assert_eq!(Tree::<i32>::new().stats(), TreeStats {
+        node_count: 0,
+        height: 0,
+        leaf_count: 0,
+        max_children: 0,
+        level_counts: vec![],
+    });
+}
+
+#[test]
+fn structural_eq() {
+    let tree = make_tree();
+
+    let mut other = Tree::new_with_root(ROOT_STR);
+    other.new_node(LAST_ROOT_CHILD_STR, LastChild, 0).unwrap();
+    other.new_node(FIRST_ROOT_CHILD_STR, FirstChild, 0).unwrap();
+
+    assert!(Tree::structural_eq(&tree, &other));
+    assert!(Tree::structural_eq(&Tree::<i32>::new(), &Tree::new()));
+
+    let mut moved = make_tree();
+    moved.move_to(LAST_ROOT_CHILD_ID, LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+    assert!(!Tree::structural_eq(&tree, &moved));
+
+    let mut changed = make_tree();
+    *changed.data_at_mut(LAST_ROOT_CHILD_ID).unwrap() = "changed";
+    assert!(!Tree::structural_eq(&tree, &changed));
+}
+
+#[test]
+fn tree_eq() {
+    let mut other = Tree::new_with_root(ROOT_STR);
+    other.new_node(LAST_ROOT_CHILD_STR, LastChild, 0).unwrap();
+    other.new_node(FIRST_ROOT_CHILD_STR, FirstChild, 0).unwrap();
+
+    assert_eq!(make_tree(), other);
+
+    other.remove(0).unwrap();
+    assert_ne!(make_tree(), other);
+    assert_eq!(Tree::new(), other);
+
+    assert_eq!(format!("{:?}", make_tree()), "[(\"root\", 2), (\"1st root child\", 0), (\"2nd root child\", 0)]");
+}
+
+#[test]
+fn subtree_hash() {
+    let mut tree = make_tree();
+    let clone = tree.clone_to(ROOT_ID, LastChild, ROOT_ID).unwrap();
+
+    assert_eq!(tree.subtree_hash(clone).unwrap(), make_tree().subtree_hash(ROOT_ID).unwrap());
+    assert_eq!(tree.subtree_hash(FIRST_ROOT_CHILD_ID).unwrap(), tree.subtree_hash(tree.first_child_of(clone).unwrap().unwrap()).unwrap());
+    assert_ne!(tree.subtree_hash(ROOT_ID).unwrap(), tree.subtree_hash(clone).unwrap());
+
+    let mut set = HashSet::new();
+    set.insert(make_tree());
+    assert!(set.contains(&make_tree()));
+    assert!(!set.contains(&tree));
+}
+
+#[test]
+fn is_isomorphic() {
+    let mut tree = Tree::new_with_root(0);
+    let a = tree.new_node(1, LastChild, 0).unwrap();
+    tree.new_nodes(vec![2, 3], LastChild, a).unwrap();
+    let b = tree.new_node(10, LastChild, 0).unwrap();
+    tree.new_nodes(vec![20, 30], LastChild, b).unwrap();
+    let c = tree.new_node(100, LastChild, 0).unwrap();
+    let c_child = tree.new_node(200, LastChild, c).unwrap();
+    tree.new_node(300, LastChild, c_child).unwrap();
+
+    assert!(tree.is_isomorphic(a, b).unwrap());
+    assert!(!tree.is_isomorphic(a, c).unwrap());
+    assert!(!tree.is_isomorphic(a, 0).unwrap());
+    assert!(tree.is_isomorphic_by(a, b, |a, b| a * 10 == *b).unwrap());
+    assert!(!tree.is_isomorphic_by(a, b, |a, b| a == b).unwrap());
+}
+
+#[test]
+fn find_pattern() {
+    let mut tree = Tree::new_with_root("call");
+    tree.new_nodes(vec!["unwrap", "x"], LastChild, 0).unwrap();
+    let nested = tree.new_node("call", LastChild, 0).unwrap();
+    tree.new_node("unwrap", LastChild, nested).unwrap();
+    let arg = tree.new_node("call", LastChild, nested).unwrap();
+    tree.new_node("y", LastChild, arg).unwrap();
+
+    let mut pattern = Tree::new_with_root(Some("call"));
+    pattern.new_nodes(vec![Some("unwrap"), None], LastChild, 0).unwrap();
+
+    let found = tree.find_pattern_by(0, &pattern, |data, pattern| match pattern { Some(pattern) => data == pattern, None => true }).unwrap();
+    assert_eq!(found, vec![nested]);
+
+    let mut exact = Tree::new_with_root("call");
+    exact.new_node("y", LastChild, 0).unwrap();
+    assert_eq!(tree.find_pattern(0, &exact).unwrap(), vec![arg]);
+    assert_eq!(tree.find_pattern(0, &Tree::new_with_root("unwrap")).unwrap().len(), 2);
+}
+
+#[test]
+fn count_where() {
+    let mut tree = make_tree();
+    tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.count_where(ROOT_ID, |data| data.ends_with("child")).unwrap(), 3);
+    assert_eq!(tree.count_where(FIRST_ROOT_CHILD_ID, |data| data.ends_with("child")).unwrap(), 2);
+    assert_eq!(tree.count_where(ROOT_ID, |_| false).unwrap(), 0);
+}
+
+#[test]
+fn next_and_prev_node() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+    let grandchild = tree.new_node("grandchild", LastChild, child).unwrap();
+
+    let order = tree.sub_tree(ROOT_ID).unwrap();
+    assert_eq!(order, vec![ROOT_ID, FIRST_ROOT_CHILD_ID, child, grandchild, LAST_ROOT_CHILD_ID]);
+
+    for pair in order.windows(2) {
+        assert_eq!(tree.next_node(pair[0]).unwrap(), Some(pair[1]));
+        assert_eq!(tree.prev_node(pair[1]).unwrap(), Some(pair[0]));
+    }
+
+    assert_eq!(tree.next_node(LAST_ROOT_CHILD_ID).unwrap(), None);
+    assert_eq!(tree.prev_node(ROOT_ID).unwrap(), None);
+}
+
+#[test]
+fn range() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+    let last_child = tree.new_node("last child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.range(child, last_child).unwrap(), vec![child, LAST_ROOT_CHILD_ID, last_child]);
+    assert_eq!(tree.range(ROOT_ID, child).unwrap(), vec![ROOT_ID, FIRST_ROOT_CHILD_ID, child]);
+    assert_eq!(tree.range(child, child).unwrap(), vec![child]);
+    assert!(tree.range(last_child, child).unwrap().is_empty());
+}
+
+#[test]
+fn indexed_tree() {
+    let mut tree = IndexedTree::from_tree(make_tree());
+    tree.add_unique_index("name", |data: &&str| data.to_string()).unwrap();
+    tree.add_index("len", |data: &&str| data.len());
+
+    assert_eq!(tree.find_by_key("name", &LAST_ROOT_CHILD_STR.to_string()).unwrap(), Some(LAST_ROOT_CHILD_ID));
+    assert_eq!(tree.find_all_by_key("len", &14usize).unwrap(), vec![FIRST_ROOT_CHILD_ID, LAST_ROOT_CHILD_ID]);
+    assert_eq!(tree.children_of(ROOT_ID).unwrap(), vec![FIRST_ROOT_CHILD_ID, LAST_ROOT_CHILD_ID]);
+    assert!(tree.find_by_key("name", &0).is_err());
+    assert!(tree.find_by_key("missing", &0).is_err());
+
+    let child = tree.new_node("child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+    assert_eq!(tree.find_by_key("name", &"child".to_string()).unwrap(), Some(child));
+    assert!(tree.new_node("child", LastChild, ROOT_ID).is_err());
+
+    tree.modify(child, |data| *data = "renamed").unwrap();
+    assert_eq!(tree.find_by_key("name", &"child".to_string()).unwrap(), None);
+    assert_eq!(tree.find_by_key("name", &"renamed".to_string()).unwrap(), Some(child));
+    assert_eq!(tree.find_all_by_key("len", &7usize).unwrap(), vec![child]);
+    assert!(tree.modify(child, |data| *data = ROOT_STR).is_err());
+    assert_eq!(tree.data_at(child).unwrap(), &"renamed");
+
+    tree.remove(LAST_ROOT_CHILD_ID).unwrap();
+    assert_eq!(tree.find_by_key("name", &"renamed".to_string()).unwrap(), None);
+    assert_eq!(tree.find_all_by_key("len", &14usize).unwrap(), vec![FIRST_ROOT_CHILD_ID]);
+
+    tree.remove_index("name").unwrap();
+    assert!(tree.find_by_key("name", &ROOT_STR.to_string()).is_err());
+
+    let mut tree = IndexedTree::from_tree(make_tree());
+    assert!(tree.add_unique_index("len", |data: &&str| data.len()).is_err());
+}
+
+#[test]
+fn multiple_indexes() {
+    let mut tree = IndexedTree::new();
+    tree.add_unique_index("id", |data: &(u32, &str)| data.0).unwrap();
+    tree.add_index("group", |data: &(u32, &str)| data.1.to_string());
+
+    let root = tree.new_root((0, "root"));
+    let a = tree.new_node((1, "leaf"), LastChild, root).unwrap();
+    let b = tree.new_node((2, "branch"), LastChild, root).unwrap();
+    let c = tree.new_node((3, "leaf"), LastChild, b).unwrap();
+
+    assert_eq!(tree.find_by_key("id", &3u32).unwrap(), Some(c));
+    assert_eq!(tree.find_all_by_key("group", &"leaf".to_string()).unwrap(), vec![a, c]);
+    assert!(matches!(tree.new_node((1, "other"), LastChild, root), Err(TreeErr::DuplicateKey)));
+    assert_eq!(tree.find_all_by_key("group", &"other".to_string()).unwrap(), Vec::<usize>::new());
+
+    tree.modify(a, |data| *data = (4, "branch")).unwrap();
+    assert_eq!(tree.find_by_key("id", &1u32).unwrap(), None);
+    assert_eq!(tree.find_by_key("id", &4u32).unwrap(), Some(a));
+    assert_eq!(tree.find_all_by_key("group", &"leaf".to_string()).unwrap(), vec![c]);
+    assert_eq!(tree.find_all_by_key("group", &"branch".to_string()).unwrap(), vec![b, a]);
+
+    tree.remove(b).unwrap();
+    assert_eq!(tree.find_by_key("id", &2u32).unwrap(), None);
+    assert_eq!(tree.find_by_key("id", &3u32).unwrap(), None);
+    assert_eq!(tree.find_all_by_key("group", &"leaf".to_string()).unwrap(), Vec::<usize>::new());
+    assert_eq!(tree.find_all_by_key("group", &"branch".to_string()).unwrap(), vec![a]);
+
+    //a removed key can be used again.
+    let d = tree.new_node((2, "leaf"), LastChild, a).unwrap();
+    assert_eq!(tree.find_by_key("id", &2u32).unwrap(), Some(d));
+    assert_eq!(tree.find_all_by_key("group", &"leaf".to_string()).unwrap(), vec![d]);
+}
+
+#[test]
+fn tree_builder() {
+    let mut builder = TreeBuilder::new();
+
+    builder.begin_node(ROOT_STR).unwrap();
+    builder.leaf(FIRST_ROOT_CHILD_STR).unwrap();
+    builder.begin_node(LAST_ROOT_CHILD_STR).unwrap();
+    assert_eq!(builder.depth(), 2);
+    builder.leaf("child").unwrap();
+    builder.end_node().unwrap();
+    builder.end_node().unwrap();
+
+    assert!(builder.end_node().is_err());
+    assert!(builder.leaf("second root").is_err());
+
+    let tree = builder.build();
+
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 2),
+        (FIRST_ROOT_CHILD_STR, 0),
+        (LAST_ROOT_CHILD_STR, 1),
+        ("child", 0),
+    ]));
+}
+
+#[test]
+fn nested() {
+    let nested = make_tree().to_nested().unwrap();
+
+    assert_eq!(nested, NestedNode {
+        data: ROOT_STR,
+        children: vec![
+            NestedNode::new(FIRST_ROOT_CHILD_STR),
+            NestedNode::new(LAST_ROOT_CHILD_STR),
+        ],
+    });
+
+    assert_eq!(Tree::from_nested(nested.clone()), make_tree());
+    assert_eq!(make_tree().into_nested(), Some(nested));
+    assert_eq!(Tree::<i32>::new().to_nested(), None);
+}
+
+#[test]
+fn unfold() {
+    //each number has the numbers it can be halved into as children.
+    let tree = Tree::unfold(8, |&n| (n, if n > 1 { vec![n / 2, n / 2] } else { vec![] }));
+
+    assert_eq!(tree.len(), 15);
+    assert!(tree_matches(&tree, vec![
+        (8, 2),
+        (4, 2),
+        (2, 2),
+        (1, 0),
+        (1, 0),
+        (2, 2),
+        (1, 0),
+        (1, 0),
+        (4, 2),
+        (2, 2),
+        (1, 0),
+        (1, 0),
+        (2, 2),
+        (1, 0),
+        (1, 0),
+    ]));
+}
+
+#[test]
+fn fold() {
+    let mut tree = Tree::new_with_root(1);
+    let child = tree.new_node(2, LastChild, 0).unwrap();
+    tree.new_nodes(vec![3, 4], LastChild, child).unwrap();
+    tree.new_node(5, LastChild, 0).unwrap();
+
+    assert_eq!(tree.fold(0, |data, children: Vec<i32>| data + children.iter().sum::<i32>()).unwrap(), 15);
+    assert_eq!(tree.fold(child, |data, children: Vec<i32>| data + children.iter().sum::<i32>()).unwrap(), 9);
+
+    let nested = tree.fold(0, |data, children: Vec<String>| {
+        if children.is_empty() { data.to_string() } else { format!("{}({})", data, children.join(" ")) }
+    }).unwrap();
+    assert_eq!(nested, "1(2(3 4) 5)");
+}
+
+#[test]
+fn map() {
+    let mut tree = make_tree();
+    tree.remove(FIRST_ROOT_CHILD_ID).unwrap();
+    let child = tree.new_node("child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    let lengths = tree.map(|data| data.len());
+
+    assert!(tree_matches(&lengths, vec![
+        (4, 1),
+        (14, 1),
+        (5, 0),
+    ]));
+    assert_eq!(lengths.data_at(child).unwrap(), &5);
+    assert!(!lengths.contains(3));
+}
+
+#[test]
+fn filter_map() {
+    let mut tree = make_tree();
+    let comment = tree.new_node("comment", LastChild, ROOT_ID).unwrap();
+    tree.new_nodes(vec!["inner", "comment"], LastChild, comment).unwrap();
+
+    let stripped = tree.filter_map(|data| if *data == "comment" { None } else { Some(data.len()) }).unwrap();
+
+    assert!(tree_matches(&stripped, vec![
+        (4, 3),
+        (14, 0),
+        (14, 0),
+        (5, 0),
+    ]));
+
+    assert!(tree.filter_map(|data| if *data == ROOT_STR { None } else { Some(*data) }).is_err());
+
+    let single = tree.filter_map(|data| if *data == "inner" { Some(*data) } else { None }).unwrap();
+    assert!(tree_matches(&single, vec![
+        ("inner", 0),
+    ]));
+}
+
+#[test]
+fn zip() {
+    let tree = make_tree();
+    let lengths = make_tree().map(|data| data.len());
+
+    let zipped = Tree::zip(tree, lengths).unwrap();
+
+    assert!(tree_matches(&zipped.map(|(data, len)| format!("{} {}", data, len)), vec![
+        (format!("{} 4", ROOT_STR), 2),
+        (format!("{} 14", FIRST_ROOT_CHILD_STR), 0),
+        (format!("{} 14", LAST_ROOT_CHILD_STR), 0),
+    ]));
+
+    let mut different = make_tree();
+    different.new_node("child", LastChild, ROOT_ID).unwrap();
+    assert!(Tree::zip(make_tree(), different).is_err());
+    assert!(Tree::zip(make_tree(), Tree::<i32>::new()).is_err());
+}
+
+#[test]
+fn from_parent_list() {
+    let tree = Tree::from_parent_list(vec![
+        (Some(2), FIRST_ROOT_CHILD_STR),
+        (Some(2), LAST_ROOT_CHILD_STR),
+        (None, ROOT_STR),
+        (Some(1), "child"),
+    ]).unwrap();
+
+    assert_eq!(tree.get_root(), Some(2));
+    assert_eq!(tree.parent_of(3).unwrap(), Some(1));
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 2),
+        (FIRST_ROOT_CHILD_STR, 0),
+        (LAST_ROOT_CHILD_STR, 1),
+        ("child", 0),
+    ]));
+
+    assert!(Tree::<i32>::from_parent_list(vec![]).unwrap().get_root().is_none());
+    assert!(matches!(Tree::from_parent_list(vec![(None, 0), (None, 1)]), Err(TreeErr::MultipleRoots)));
+    assert!(matches!(Tree::from_parent_list(vec![(Some(1), 0), (Some(0), 1)]), Err(TreeErr::NoRoot)));
+    assert!(matches!(Tree::from_parent_list(vec![(None, 0), (Some(2), 1), (Some(1), 2)]), Err(TreeErr::Cycle)));
+    assert!(matches!(Tree::from_parent_list(vec![(None, 0), (Some(5), 1)]), Err(TreeErr::InvalidId)));
+}
+
+#[test]
+fn to_parent_list() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", FirstChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.to_parent_list(), vec![
+        (ROOT_ID, None, &ROOT_STR),
+        (FIRST_ROOT_CHILD_ID, Some(ROOT_ID), &FIRST_ROOT_CHILD_STR),
+        (LAST_ROOT_CHILD_ID, Some(ROOT_ID), &LAST_ROOT_CHILD_STR),
+        (child, Some(LAST_ROOT_CHILD_ID), &"child"),
+    ]);
+
+    let rows: Vec<_> = tree.to_parent_list().into_iter().map(|(_, parent, data)| (parent, *data)).collect();
+    assert_eq!(Tree::from_parent_list(rows).unwrap(), tree);
+}
+
+#[test]
+fn nested_set() {
+    let mut tree = make_tree();
+    tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    let rows = tree.to_nested_set();
+
+    assert_eq!(rows, vec![
+        (1, 8, &ROOT_STR),
+        (2, 5, &FIRST_ROOT_CHILD_STR),
+        (3, 4, &"child"),
+        (6, 7, &LAST_ROOT_CHILD_STR),
+    ]);
+
+    let mut rows: Vec<_> = rows.into_iter().map(|(left, right, data)| (left, right, *data)).collect();
+    rows.reverse();
+    assert_eq!(Tree::from_nested_set(rows).unwrap(), tree);
+
+    assert!(matches!(Tree::from_nested_set(vec![(1, 4, 0), (2, 5, 1)]), Err(TreeErr::InvalidNesting)));
+    assert!(matches!(Tree::from_nested_set(vec![(1, 2, 0), (3, 4, 1)]), Err(TreeErr::MultipleRoots)));
+    assert!(matches!(Tree::from_nested_set(vec![(2, 1, 0)]), Err(TreeErr::InvalidNesting)));
+}
+
+#[test]
+fn to_closure_table() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.to_closure_table(), vec![
+        (ROOT_ID, ROOT_ID, 0),
+        (FIRST_ROOT_CHILD_ID, FIRST_ROOT_CHILD_ID, 0),
+        (ROOT_ID, FIRST_ROOT_CHILD_ID, 1),
+        (LAST_ROOT_CHILD_ID, LAST_ROOT_CHILD_ID, 0),
+        (ROOT_ID, LAST_ROOT_CHILD_ID, 1),
+        (child, child, 0),
+        (LAST_ROOT_CHILD_ID, child, 1),
+        (ROOT_ID, child, 2),
+    ]);
+    assert!(Tree::<i32>::new().to_closure_table().is_empty());
+}
+
+#[test]
+fn from_paths() {
+    let paths = vec!["src/main.rs", "src/tree/mod.rs", "Cargo.toml", "/src//lib.rs"];
+    let tree = Tree::from_paths(String::from("."), paths, "/", |part| part.to_string());
+
+    assert!(tree_matches(&tree, vec![
+        (String::from("."), 2),
+        (String::from("src"), 3),
+        (String::from("main.rs"), 0),
+        (String::from("tree"), 1),
+        (String::from("mod.rs"), 0),
+        (String::from("lib.rs"), 0),
+        (String::from("Cargo.toml"), 0),
+    ]));
+}
+
+#[test]
+fn from_indented() {
+    let text = "root\n    1st root child\n        child\n\n    2nd root child\n";
+    let tree = Tree::from_indented(text, |line| line.to_string()).unwrap();
+
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR.to_string(), 2),
+        (FIRST_ROOT_CHILD_STR.to_string(), 1),
+        ("child".to_string(), 0),
+        (LAST_ROOT_CHILD_STR.to_string(), 0),
+    ]));
+
+    assert!(Tree::from_indented("a\nb", |line| line.to_string()).is_err());
+    assert!(Tree::from_indented("", |line| line.to_string()).unwrap().get_root().is_none());
+}
+
+#[test]
+fn to_indented() {
+    let mut tree = make_tree();
+    tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    let mut text = String::new();
+    tree.to_indented(&mut text, |data| *data).unwrap();
+
+    assert_eq!(text, "root\n    1st root child\n        child\n    2nd root child\n");
+    assert_eq!(Tree::from_indented(&text, |line| line.to_string()).unwrap(), tree.map(|data| data.to_string()));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn from_dir() {
+    let dir = std::env::temp_dir().join(format!("tree_from_dir_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("sub/deeper")).unwrap();
+    std::fs::write(dir.join("a.txt"), "abc").unwrap();
+    std::fs::write(dir.join("sub/b.txt"), "").unwrap();
+    std::fs::write(dir.join("sub/deeper/c.txt"), "").unwrap();
+
+    let tree = Tree::from_dir(&dir, &DirOptions::default()).unwrap();
+    let mut names = tree.map(|entry| entry.name.clone());
+    *names.data_at_mut(ROOT_ID).unwrap() = String::from("root");
+
+    assert!(tree_matches(&names, vec![
+        (String::from("root"), 2),
+        (String::from("a.txt"), 0),
+        (String::from("sub"), 2),
+        (String::from("b.txt"), 0),
+        (String::from("deeper"), 1),
+        (String::from("c.txt"), 0),
+    ]));
+    assert_eq!(tree.data_at(tree.nth_child(0, 0).unwrap().unwrap()).unwrap().len, 3);
+
+    let options = DirOptions {
+        max_depth: Some(2),
+        filter: Some(Box::new(|entry| entry.name != "a.txt")),
+        ..DirOptions::default()
+    };
+    let tree = Tree::from_dir(&dir, &options).unwrap();
+    assert_eq!(tree.len(), 4);
+
+    //a link back up to the root is kept as an entry without being read again.
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&dir, dir.join("sub/loop")).unwrap();
+        let options = DirOptions { follow_links: true, ..DirOptions::default() };
+        let tree = Tree::from_dir(&dir, &options).unwrap();
+        assert_eq!(tree.len(), 7);
+        let sub = tree.nth_child(ROOT_ID, 1).unwrap().unwrap();
+        let link = tree.nth_child(sub, 2).unwrap().unwrap();
+        assert_eq!(tree.data_at(link).unwrap().name, "loop");
+        assert!(tree.data_at(link).unwrap().is_dir);
+        assert_eq!(tree.child_count(link).unwrap(), 0);
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "petgraph")]
+#[test]
+fn petgraph() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    let (graph, indices) = tree.to_graph();
+    assert_eq!(graph.node_count(), 4);
+    assert_eq!(graph.edge_count(), 3);
+    assert_eq!(graph[indices[&child]], "child");
+
+    let dominators = petgraph::algo::dominators::simple_fast(&graph, indices[&ROOT_ID]);
+    assert_eq!(dominators.immediate_dominator(indices[&child]), Some(indices[&FIRST_ROOT_CHILD_ID]));
+
+    assert!(tree_matches(&Tree::from_graph(graph).unwrap(), vec![
+        (ROOT_STR, 2),
+        (FIRST_ROOT_CHILD_STR, 1),
+        ("child", 0),
+        (LAST_ROOT_CHILD_STR, 0),
+    ]));
+
+    let mut graph = petgraph::graph::DiGraph::<i32, ()>::new();
+    let a = graph.add_node(0);
+    let b = graph.add_node(1);
+    let c = graph.add_node(2);
+    graph.add_edge(a, c, ());
+    graph.add_edge(b, c, ());
+    assert!(matches!(Tree::from_graph(graph), Err(TreeErr::MultipleParents)));
+}
+
+#[cfg(feature = "ego-tree")]
+#[test]
+fn ego_tree() {
+    use std::convert::TryFrom;
+
+    let mut other = ego_tree::Tree::new(ROOT_STR);
+    other.root_mut().append(LAST_ROOT_CHILD_STR);
+    other.root_mut().prepend(FIRST_ROOT_CHILD_STR).append("child");
+    other.orphan("orphan");
+
+    let tree = Tree::from(other);
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 2),
+        (FIRST_ROOT_CHILD_STR, 1),
+        ("child", 0),
+        (LAST_ROOT_CHILD_STR, 0),
+    ]));
+
+    let other = ego_tree::Tree::try_from(tree).unwrap();
+    assert_eq!(other.root().descendants().map(|node| *node.value()).collect::<Vec<_>>(), [ROOT_STR, FIRST_ROOT_CHILD_STR, "child", LAST_ROOT_CHILD_STR]);
+
+    assert!(matches!(ego_tree::Tree::try_from(Tree::<i32>::new()), Err(TreeErr::NoRoot)));
+}
+
+#[cfg(feature = "indextree")]
+#[test]
+fn indextree() {
+    use std::convert::TryFrom;
+
+    let mut tree = make_tree();
+    tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    let arena = indextree::Arena::from(tree);
+    let root = arena.iter_node_ids().next().unwrap();
+    assert_eq!(root.descendants(&arena).map(|id| *arena[id].get()).collect::<Vec<_>>(), [ROOT_STR, FIRST_ROOT_CHILD_STR, "child", LAST_ROOT_CHILD_STR]);
+
+    assert!(tree_matches(&Tree::try_from(arena).unwrap(), vec![
+        (ROOT_STR, 2),
+        (FIRST_ROOT_CHILD_STR, 1),
+        ("child", 0),
+        (LAST_ROOT_CHILD_STR, 0),
+    ]));
+
+    let mut arena = indextree::Arena::new();
+    let first = arena.new_node(0);
+    first.append_value(1, &mut arena);
+    arena.new_node(2);
+    first.append_value(3, &mut arena).remove(&mut arena);
+
+    let forest = Forest::from(arena);
+    assert_eq!(forest.len(), 3);
+    assert_eq!(forest.roots().iter().map(|&id| *forest.data_at(id).unwrap()).collect::<Vec<_>>(), [0, 2]);
+
+    let arena = indextree::Arena::from(forest);
+    assert_eq!(arena.roots().count(), 2);
+    assert!(matches!(Tree::try_from(arena), Err(TreeErr::MultipleRoots)));
+}
+
+#[test]
+fn edges() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.to_edges(), [
+        (ROOT_ID, FIRST_ROOT_CHILD_ID, &FIRST_ROOT_CHILD_STR),
+        (FIRST_ROOT_CHILD_ID, child, &"child"),
+        (ROOT_ID, LAST_ROOT_CHILD_ID, &LAST_ROOT_CHILD_STR),
+    ]);
+
+    let tree = Tree::from_edges(vec![ROOT_STR, LAST_ROOT_CHILD_STR, FIRST_ROOT_CHILD_STR, "child"], vec![(0, 2), (2, 3), (0, 1)]).unwrap();
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR, 2),
+        (FIRST_ROOT_CHILD_STR, 1),
+        ("child", 0),
+        (LAST_ROOT_CHILD_STR, 0),
+    ]));
+
+    assert!(matches!(Tree::from_edges(vec![0, 1], vec![(0, 2)]), Err(TreeErr::InvalidId)));
+    assert!(matches!(Tree::from_edges(vec![0, 1, 2], vec![(0, 2), (1, 2)]), Err(TreeErr::MultipleParents)));
+    assert!(matches!(Tree::from_edges(vec![0, 1, 2], vec![(0, 1)]), Err(TreeErr::MultipleRoots)));
+    assert!(matches!(Tree::from_edges(vec![0, 1], vec![(0, 1), (1, 0)]), Err(TreeErr::NoRoot)));
+    assert!(matches!(Tree::from_edges(vec![0, 1, 2], vec![(1, 2), (2, 1)]), Err(TreeErr::Cycle)));
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn random() {
+    use rand::{SeedableRng, Rng};
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+
+    for _ in 0..20 {
+        let tree = Tree::random(&mut rng, 50, 3, |rng| rng.gen_range(0..100));
+
+        assert_eq!(tree.len(), 50);
+        assert!(tree.stats().max_children <= 3);
+        assert_eq!(tree.subtree_size(tree.get_root().unwrap()).unwrap(), 50);
+    }
+
+    assert_eq!(Tree::random(&mut rng, 10, 0, |_| 0).len(), 1);
+    assert_eq!(Tree::random(&mut rng, 0, 3, |_| 0).len(), 0);
+}
+
+#[cfg(feature = "proptest")]
+#[test]
+fn arbitrary() {
+    use proptest::prelude::*;
+    use proptest::test_runner::{TestRunner, TestError};
+
+    let mut runner = TestRunner::deterministic();
+    let result = runner.run(&any::<Tree<u8>>(), |tree| {
+        prop_assert!(tree.len() < 5);
+        Ok(())
+    });
+
+    match result {
+        Err(TestError::Fail(_, tree)) => {
+            assert_eq!(tree.len(), 5);
+            assert!(tree.sub_tree(tree.get_root().unwrap()).unwrap().into_iter().all(|id| *tree.data_at(id).unwrap() == 0));
+        }
+        _ => panic!("the property should fail for large trees"),
+    }
+
+    let mut runner = TestRunner::deterministic();
+    runner.run(&TreeStrategy::new(0..10i32, 1..20), |tree| {
+        prop_assert!(tree.len() >= 1 && tree.len() < 20);
+        prop_assert_eq!(tree.subtree_size(tree.get_root().unwrap()).unwrap(), tree.len());
+        Ok(())
+    }).unwrap();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_nested() {
+    let mut tree = make_tree();
+    tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    let json = serde_json::to_string(&tree).unwrap();
+    assert_eq!(json, r#"{"data":"root","children":[{"data":"1st root child","children":[{"data":"child","children":[]}]},{"data":"2nd root child","children":[]}]}"#);
+
+    let tree: Tree<String> = serde_json::from_str(&json).unwrap();
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR.to_string(), 2),
+        (FIRST_ROOT_CHILD_STR.to_string(), 1),
+        ("child".to_string(), 0),
+        (LAST_ROOT_CHILD_STR.to_string(), 0),
+    ]));
+
+    assert_eq!(serde_json::to_string(&Tree::<i32>::new()).unwrap(), "null");
+    assert_eq!(serde_json::from_str::<Tree<i32>>("null").unwrap().len(), 0);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_flat() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", FirstChild, LAST_ROOT_CHILD_ID).unwrap();
+    tree.remove(FIRST_ROOT_CHILD_ID).unwrap();
+
+    let json = serde_json::to_string(&FlatTree(tree)).unwrap();
+    assert_eq!(json, r#"{"nodes":[{"id":0,"parent":null,"data":"root"},{"id":2,"parent":0,"data":"2nd root child"},{"id":3,"parent":2,"data":"child"}]}"#);
+
+    let mut tree: Tree<String> = serde_json::from_str::<FlatTree<String>>(&json).unwrap().into();
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR.to_string(), 1),
+        (LAST_ROOT_CHILD_STR.to_string(), 1),
+        ("child".to_string(), 0),
+    ]));
+    assert_eq!(tree.parent_of(child).unwrap(), Some(LAST_ROOT_CHILD_ID));
+    assert!(!tree.contains(FIRST_ROOT_CHILD_ID));
+    assert_eq!(tree.new_node("new".to_string(), LastChild, ROOT_ID).unwrap(), FIRST_ROOT_CHILD_ID);
+
+    assert!(serde_json::from_str::<FlatTree<i32>>(r#"{"nodes":[{"id":1,"parent":0,"data":1},{"id":0,"parent":null,"data":0}]}"#).is_err());
+    assert!(serde_json::from_str::<FlatTree<i32>>(r#"{"nodes":[{"id":0,"parent":null,"data":0},{"id":0,"parent":0,"data":1}]}"#).is_err());
+    assert!(serde_json::from_str::<FlatTree<i32>>(r#"{"nodes":[{"id":18446744073709551615,"parent":null,"data":0}]}"#).is_err());
+    assert!(serde_json::from_str::<FlatTree<i32>>(r#"{"nodes":[{"id":0,"parent":null,"data":0},{"id":1000000,"parent":0,"data":1}]}"#).is_err());
+    assert_eq!(serde_json::from_str::<FlatTree<i32>>(r#"{"nodes":[{"id":40,"parent":null,"data":0}]}"#).unwrap().0.free_count(), 40);
+
+    //deep trees don't overflow the stack.
+    let mut tree = Tree::new_with_root(0);
+    let mut parent = ROOT_ID;
+    for i in 1..10_000 {
+        parent = tree.new_node(i, LastChild, parent).unwrap();
+    }
+    let json = serde_json::to_string(&FlatTree(tree)).unwrap();
+    assert_eq!(serde_json::from_str::<FlatTree<i32>>(&json).unwrap().0.len(), 10_000);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json() {
+    let tree = make_tree();
+    let json = tree.to_json_string().unwrap();
+    assert_eq!(json, r#"{"data":"root","children":[{"data":"1st root child","children":[]},{"data":"2nd root child","children":[]}]}"#);
+
+    let tree = Tree::<String>::from_json_str(&json).unwrap();
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR.to_string(), 2),
+        (FIRST_ROOT_CHILD_STR.to_string(), 0),
+        (LAST_ROOT_CHILD_STR.to_string(), 0),
+    ]));
+
+    assert!(Tree::<i32>::from_json_str(r#"{"data":"text","children":[]}"#).is_err());
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn rkyv() {
+    let mut tree = Tree::new_with_root(String::from(ROOT_STR));
+    tree.new_node(String::from(FIRST_ROOT_CHILD_STR), LastChild, ROOT_ID).unwrap();
+    tree.new_node(String::from(LAST_ROOT_CHILD_STR), LastChild, ROOT_ID).unwrap();
+    let child = tree.new_node(String::from("child"), LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+    tree.remove(LAST_ROOT_CHILD_ID).unwrap();
+
+    let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&tree).unwrap();
+    let archived = rkyv::access::<ArchivedTree<String>, rkyv::rancor::Error>(&bytes).unwrap();
+
+    assert_eq!(archived.len(), 3);
+    assert_eq!(archived.get_root(), Some(ROOT_ID));
+    assert_eq!(archived.sub_tree(ROOT_ID).unwrap(), [ROOT_ID, FIRST_ROOT_CHILD_ID, child]);
+    assert_eq!(archived.data_at(child).unwrap().as_str(), "child");
+    assert_eq!(archived.parent_of(child).unwrap(), Some(FIRST_ROOT_CHILD_ID));
+    assert!(matches!(archived.data_at(LAST_ROOT_CHILD_ID), Err(TreeErr::InvalidId)));
+    assert_eq!(archived.child_count(ROOT_ID).unwrap(), 1);
+    assert_eq!(archived.depth_of(child).unwrap(), 2);
+    assert!(archived.is_leaf(child).unwrap());
+    assert!(archived.is_root(ROOT_ID).unwrap());
+    assert!(!archived.is_root(child).unwrap());
+    assert!(archived.is_ancestor_of(ROOT_ID, child).unwrap());
+    assert!(!archived.is_descendant_of(ROOT_ID, child).unwrap());
+    assert_eq!(archived.path_to_root(child).unwrap(), [child, FIRST_ROOT_CHILD_ID, ROOT_ID]);
+    assert_eq!(archived.next_node(FIRST_ROOT_CHILD_ID).unwrap(), Some(child));
+    assert_eq!(archived.next_node(child).unwrap(), None);
+    assert_eq!(archived.prev_node(child).unwrap(), Some(FIRST_ROOT_CHILD_ID));
+    assert_eq!(archived.prev_node(ROOT_ID).unwrap(), None);
+    assert!(matches!(archived.next_node(LAST_ROOT_CHILD_ID), Err(TreeErr::InvalidId)));
+
+    let tree = rkyv::deserialize::<Tree<String>, rkyv::rancor::Error>(archived).unwrap();
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR.to_string(), 1),
+        (FIRST_ROOT_CHILD_STR.to_string(), 1),
+        ("child".to_string(), 0),
+    ]));
+}
+
+#[test]
+fn pretty_print() {
+    let mut tree = make_tree();
+    let child = tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+    tree.new_node("grandchild", LastChild, child).unwrap();
+
+    let mut text = String::new();
+    tree.pretty_print(&mut text, &PrettyOptions::default(), |data| *data).unwrap();
+    assert_eq!(text, "root\n├── 1st root child\n│   └── child\n│       └── grandchild\n└── 2nd root child\n");
+
+    let mut bytes = Vec::new();
+    tree.pretty_print_io(&mut bytes, &PrettyOptions { indent: 2, max_depth: Some(1) }, |data| data.len()).unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(), "4\n├ 14\n└ 14\n");
+
+    let mut narrow = String::new();
+    tree.pretty_print(&mut narrow, &PrettyOptions { indent: 0, max_depth: Some(1) }, |data| data.len()).unwrap();
+    assert_eq!(narrow, "4\n├ 14\n└ 14\n");
+
+    struct Unrenderable;
+    impl std::fmt::Display for Unrenderable {
+        fn fmt(&self, _: &mut std::fmt::Formatter) -> std::fmt::Result { Err(std::fmt::Error) }
+    }
+    let err = tree.pretty_print_io(&mut Vec::new(), &PrettyOptions::default(), |_| Unrenderable).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Other);
+
+    let mut full = [0u8; 8];
+    let err = tree.pretty_print_io(&mut &mut full[..], &PrettyOptions::default(), |data| *data).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+    assert_eq!(&full, "root\n├".as_bytes());
+
+    let mut from = String::new();
+    tree.pretty_print_from(FIRST_ROOT_CHILD_ID, &mut from, &PrettyOptions { indent: 2, max_depth: Some(1) }, |data| *data).unwrap().unwrap();
+    assert_eq!(from, "1st root child\n└ child\n");
+
+    let mut bytes = Vec::new();
+    tree.pretty_print_io_from(child, &mut bytes, &PrettyOptions::default(), |data| *data).unwrap().unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(), "child\n└── grandchild\n");
+
+    let mut untouched = String::new();
+    assert!(matches!(tree.pretty_print_from(100, &mut untouched, &PrettyOptions::default(), |data| *data), Err(TreeErr::InvalidId)));
+    assert!(matches!(tree.pretty_print_io_from(100, &mut Vec::new(), &PrettyOptions::default(), |data| *data), Err(TreeErr::InvalidId)));
+    assert!(untouched.is_empty());
+}
+
+#[test]
+fn to_html() {
+    let mut tree = make_tree();
+    tree.new_node("<child> & \"more\"", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    assert_eq!(tree.to_html(ROOT_ID, |data| *data).unwrap(), concat!(
+        "<ul><li>root<ul>",
+        "<li>1st root child<ul><li>&lt;child&gt; &amp; &quot;more&quot;</li></ul></li>",
+        "<li>2nd root child</li>",
+        "</ul></li></ul>",
+    ));
+    assert_eq!(tree.to_html(LAST_ROOT_CHILD_ID, |data| data.len()).unwrap(), "<ul><li>14</li></ul>");
+    assert!(matches!(tree.to_html(10, |data| *data), Err(TreeErr::InvalidId)));
+}
+
+#[cfg(feature = "xml")]
+#[test]
+fn xml() {
+    let mut tree = make_tree();
+    tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    let xml = tree.to_xml(|data| XmlElement {
+        tag: String::from("node"),
+        attributes: vec![(String::from("len"), data.len().to_string())],
+        text: data.to_string(),
+    });
+    assert_eq!(xml, r#"<node len="4">root<node len="14">1st root child<node len="5">child</node></node><node len="14">2nd root child</node></node>"#);
+
+    let tree = Tree::from_xml(&xml, |element| element.text).unwrap();
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR.to_string(), 2),
+        (FIRST_ROOT_CHILD_STR.to_string(), 1),
+        ("child".to_string(), 0),
+        (LAST_ROOT_CHILD_STR.to_string(), 0),
+    ]));
+
+    let tree = Tree::from_xml("<?xml version=\"1.0\"?>\n<a x=\"&lt;1&gt;\">\n  <b/>\n  <!-- comment -->\n  <c><![CDATA[<text>]]></c>\n</a>", |element| element).unwrap();
+    assert_eq!(tree.data_at(ROOT_ID).unwrap().attributes, [(String::from("x"), String::from("<1>"))]);
+    let children = tree.children_of(ROOT_ID).unwrap();
+    assert_eq!(tree.data_at(children[0]).unwrap(), &XmlElement::new("b"));
+    assert_eq!(tree.data_at(children[1]).unwrap().text, "<text>");
+
+    assert!(matches!(Tree::from_xml("<a/><b/>", |element| element), Err(XmlErr::Tree(TreeErr::MultipleRoots))));
+    assert!(matches!(Tree::from_xml("<a><b></a>", |element| element), Err(XmlErr::Xml(_))));
+    assert!(Tree::from_xml("<a><b>", |element| element).is_err());
+    assert_eq!(Tree::from_xml("", |element| element).unwrap().len(), 0);
+}
+
+#[test]
+fn sexpr() {
+    let mut tree = make_tree();
+    tree.new_node("child", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+
+    let sexpr = tree.to_sexpr(|data| *data);
+    assert_eq!(sexpr, r#"(root ("1st root child" child) "2nd root child")"#);
+
+    let tree = Tree::from_sexpr(&sexpr, |atom| atom.to_string()).unwrap();
+    assert!(tree_matches(&tree, vec![
+        (ROOT_STR.to_string(), 2),
+        (FIRST_ROOT_CHILD_STR.to_string(), 1),
+        ("child".to_string(), 0),
+        (LAST_ROOT_CHILD_STR.to_string(), 0),
+    ]));
+
+    let tree = Tree::from_sexpr("(+ 1\n  (* 2 3))", |atom| atom.to_string()).unwrap();
+    assert_eq!(tree.to_sexpr(|data| data.clone()), "(+ 1 (* 2 3))");
+    assert_eq!(Tree::from_sexpr(r#""a \"quote\"""#, |atom| atom.to_string()).unwrap().to_sexpr(|data| data.clone()), r#""a \"quote\"""#);
+
+    assert!(matches!(Tree::from_sexpr("(a b", |atom| atom.to_string()), Err(TreeErr::InvalidNesting)));
+    assert!(matches!(Tree::from_sexpr("(a b))", |atom| atom.to_string()), Err(TreeErr::InvalidNesting)));
+    assert!(matches!(Tree::from_sexpr("((a) b)", |atom| atom.to_string()), Err(TreeErr::InvalidNesting)));
+    assert!(matches!(Tree::from_sexpr("a b", |atom| atom.to_string()), Err(TreeErr::MultipleRoots)));
+    assert_eq!(Tree::from_sexpr("", |atom| atom.to_string()).unwrap().len(), 0);
+}
+
+#[cfg(feature = "newick")]
+#[test]
+fn newick() {
+    let mut tree = Tree::new_with_root(NewickNode::new("root"));
+    let first = tree.new_node(NewickNode { label: String::from("a"), length: Some(0.5) }, LastChild, ROOT_ID).unwrap();
+    tree.new_node(NewickNode { label: String::from("b's leaf"), length: Some(1.0) }, LastChild, first).unwrap();
+    tree.new_node(NewickNode::new(""), LastChild, first).unwrap();
+    tree.new_node(NewickNode { label: String::from("c"), length: Some(2.25) }, LastChild, ROOT_ID).unwrap();
+
+    let newick = tree.to_newick(|node| node.clone());
+    assert_eq!(newick, "(('b''s leaf':1,)a:0.5,c:2.25)root;");
+
+    let read = Tree::from_newick(&newick, |node| node).unwrap();
+    assert!(read == tree);
+
+    let tree = Tree::from_newick("((A_1:0.1, B:0.2) [comment] E:0.5, C) F;", |node| node.label).unwrap();
+    assert!(tree_matches(&tree, vec![
+        (String::from("F"), 2),
+        (String::from("E"), 2),
+        (String::from("A 1"), 0),
+        (String::from("B"), 0),
+        (String::from("C"), 0),
+    ]));
+
+    assert!(matches!(Tree::from_newick("(a,b;", |node| node), Err(TreeErr::InvalidNesting)));
+    assert!(matches!(Tree::from_newick("a,b;", |node| node), Err(TreeErr::InvalidNesting)));
+    assert!(matches!(Tree::from_newick("a:x;", |node| node), Err(TreeErr::InvalidNesting)));
+    assert!(matches!(Tree::from_newick("a; b;", |node| node), Err(TreeErr::MultipleRoots)));
+    assert_eq!(Tree::from_newick("", |node| node).unwrap().len(), 0);
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn varint_child_counts() {
+    let mut tree = Tree::new_with_root(1u8);
+    tree.new_node(2, LastChild, ROOT_ID).unwrap();
+    tree.new_node(3, LastChild, ROOT_ID).unwrap();
+
+    assert_eq!(tree.into_bytes().collect::<Vec<u8>>(), [3, 1, 2, 2, 0, 3, 0]);
+
+    let mut tree = Tree::new_with_root(0u8);
+    tree.new_nodes(0..200, LastChild, ROOT_ID).unwrap();
+
+    let bytes: Vec<u8> = tree.into_bytes().collect();
+    assert_eq!(&bytes[..5], [0xc9, 0x01, 0, 0xc8, 0x01]);
+    assert_eq!(bytes.len(), 5 + 200 * 2);
+
+    let new_tree = Tree::<u8>::from_bytes(&mut bytes.into_iter()).unwrap();
+    assert!(new_tree == tree);
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn huge_child_counts() {
+    //a child count of u32::MAX + 1 used to be cut down to 0.
+    let bytes = vec![1, 7, 0x80, 0x80, 0x80, 0x80, 0x10];
+    assert!(Tree::<u8>::from_bytes(&mut bytes.into_iter()).is_err());
+
+    //counts bigger than a usize don't wrap around to small numbers either.
+    let mut bytes = vec![1, 7];
+    bytes.extend([0x80; 10]);
+    bytes.push(0x01);
+    assert!(Tree::<u8>::from_bytes(&mut bytes.into_iter()).is_err());
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn checked_bytes() {
+    let mut tree = Tree::new_with_root(1i32);
+    tree.new_nodes(2..10, LastChild, ROOT_ID).unwrap();
+
+    let bytes: Vec<u8> = tree.into_checked_bytes().collect();
+    let plain: Vec<u8> = tree.into_bytes().collect();
+    assert_eq!(bytes.len(), plain.len() + 4);
+    assert_eq!(bytes[..plain.len()], plain[..]);
+
+    let new_tree = Tree::<i32>::from_checked_bytes(&mut bytes.clone().into_iter()).unwrap();
+    assert!(new_tree == tree);
+    let new_tree = Tree::<i32>::from_checked_io_bytes(&mut bytes.clone().into_iter().map(Ok)).unwrap();
+    assert!(new_tree == tree);
+
+    //the standard CRC-32 of the bytes 1, 7, 0.
+    let mut bytes: Vec<u8> = Tree::new_with_root(7u8).into_checked_bytes().collect();
+    assert_eq!(bytes, [1, 7, 0, 0xe2, 0x25, 0xc2, 0xb1]);
+
+    let mut corrupted: Vec<u8> = tree.into_checked_bytes().collect();
+    corrupted[6] ^= 0x10;
+    assert!(matches!(Tree::<i32>::from_checked_bytes(&mut corrupted.into_iter()), Err(ChecksumErr::Corrupted)));
+
+    //changing the child count of the root leaves bytes that can't be read as a tree at all.
+    let mut unreadable: Vec<u8> = tree.into_checked_bytes().collect();
+    unreadable[5] = 0x7f;
+    assert!(matches!(Tree::<i32>::from_checked_bytes(&mut unreadable.clone().into_iter()), Err(ChecksumErr::Corrupted)));
+    assert!(matches!(Tree::<i32>::from_checked_io_bytes(&mut unreadable.into_iter().map(Ok)), Err(ChecksumErr::Corrupted)));
+
+    //but a failed read is still the io error.
+    let mut failing = plain.iter().take(3).map(|&byte| Ok(byte)).chain(std::iter::once(Err(std::io::Error::other("failed"))));
+    assert!(matches!(Tree::<i32>::from_checked_io_bytes(&mut failing), Err(ChecksumErr::Bytes(_))));
+
+    bytes.truncate(3);
+    assert!(matches!(Tree::<u8>::from_checked_bytes(&mut bytes.into_iter()), Err(ChecksumErr::Bytes(_))));
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn compressed_bytes() {
+    let mut tree = Tree::new_with_root(String::from("root"));
+    for _ in 0..2000 {
+        tree.new_node(String::from("the same payload over and over"), LastChild, ROOT_ID).unwrap();
+    }
+
+    let plain = tree.into_bytes().count();
+    let bytes: Vec<u8> = tree.into_bytes_compressed().collect();
+    assert!(bytes.len() * 10 < plain);
+
+    let new_tree = Tree::<String>::from_bytes_compressed(&mut bytes.clone().into_iter()).unwrap();
+    assert!(new_tree == tree);
+
+    //only the compressed bytes of each tree are taken, so trees can be read back to back.
+    let small = Tree::new_with_root(String::from("small"));
+    let mut both = bytes.clone();
+    both.extend(small.into_bytes_compressed());
+    both.push(42);
+    let mut iter = both.into_iter();
+    assert!(Tree::<String>::from_bytes_compressed(&mut iter).unwrap() == tree);
+    assert!(Tree::<String>::from_bytes_compressed(&mut iter).unwrap() == small);
+    assert_eq!(iter.collect::<Vec<u8>>(), [42]);
+
+    assert!(Tree::<String>::from_bytes_compressed(&mut vec![1, 2, 3].into_iter()).is_err());
+    assert!(Tree::<String>::from_bytes_compressed(&mut bytes[..bytes.len() / 2].iter().copied()).is_err());
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn write_to_read_from() {
+    let mut tree = Tree::new_with_root(0i32);
+    for i in 1..5000 {
+        tree.new_node(i, LastChild, (i as usize - 1) / 4).unwrap();
+    }
+
+    let mut bytes = Vec::new();
+    tree.write_to(&mut bytes).unwrap();
+    assert_eq!(bytes, tree.into_bytes().collect::<Vec<u8>>());
+
+    let new_tree = Tree::<i32>::read_from(&bytes[..]).unwrap();
+    assert!(new_tree == tree);
+
+    assert!(Tree::<i32>::read_from(&bytes[..10]).is_err());
+
+    //the bytes after the tree are left in the reader.
+    let small = Tree::new_with_root(7i32);
+    small.write_to(&mut bytes).unwrap();
+    bytes.push(42);
+    let mut read = std::io::BufReader::with_capacity(64, &bytes[..]);
+    assert!(Tree::<i32>::read_from(&mut read).unwrap() == tree);
+    assert!(Tree::<i32>::read_from(&mut read).unwrap() == small);
+    assert_eq!(std::io::Read::bytes(read).map(Result::unwrap).collect::<Vec<u8>>(), [42]);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_stream() {
+    let mut tree = Tree::new_with_root(0i32);
+    for i in 1..5000 {
+        tree.new_node(i, LastChild, (i as usize - 1) / 4).unwrap();
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+    fn is_send<T: Send>(value: T) -> T { value }
+
+    let mut bytes = Vec::new();
+    runtime.block_on(is_send(tree.write_to_async(&mut bytes))).unwrap();
+    assert_eq!(bytes, tree.into_bytes().collect::<Vec<u8>>());
+
+    let new_tree = runtime.block_on(Tree::<i32>::read_from_async(std::io::Cursor::new(bytes.clone()))).unwrap();
+    assert!(new_tree == tree);
+
+    bytes.truncate(10);
+    assert!(runtime.block_on(Tree::<i32>::read_from_async(std::io::Cursor::new(bytes))).is_err());
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn lazy_tree() {
+    let mut tree = Tree::new_with_root(0i32);
+    let first = tree.new_node(1, LastChild, ROOT_ID).unwrap();
+    tree.new_nodes(10..15, LastChild, first).unwrap();
+    let second = tree.new_node(2, LastChild, ROOT_ID).unwrap();
+    let deep = tree.new_node(20, LastChild, second).unwrap();
+    tree.new_node(200, LastChild, deep).unwrap();
+
+    let bytes: Vec<u8> = tree.into_bytes().collect();
+    let mut lazy = LazyTree::<i32>::new(&bytes).unwrap();
+
+    assert_eq!(lazy.len(), 1);
+    assert!(!lazy.is_expanded(ROOT_ID));
+
+    let children = lazy.expand(ROOT_ID).unwrap();
+    assert_eq!(children.iter().map(|&id| *lazy.data_at(id).unwrap()).collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(lazy.len(), 3);
+
+    let grandchildren = lazy.expand(children[1]).unwrap();
+    assert_eq!(*lazy.data_at(grandchildren[0]).unwrap(), 20);
+    assert!(lazy.is_expanded(ROOT_ID));
+    assert!(!lazy.is_expanded(children[0]));
+    assert_eq!(lazy.expand(ROOT_ID).unwrap(), children);
+    assert!(matches!(lazy.expand(100), Err(LazyErr::Tree(TreeErr::InvalidId))));
+
+    assert!(lazy.into_tree().unwrap() == tree);
+
+    let lazy = LazyTree::<i32>::new(&bytes[..bytes.len() - 3]).unwrap();
+    assert!(matches!(lazy.into_tree(), Err(LazyErr::Bytes(_))));
+
+    //a node whose last child can't be read doesn't keep the children before it.
+    let mut tree = Tree::new_with_root(0i32);
+    tree.new_nodes(1..4, LastChild, ROOT_ID).unwrap();
+    let bytes: Vec<u8> = tree.into_bytes().collect();
+    let mut lazy = LazyTree::<i32>::new(&bytes[..bytes.len() - 3]).unwrap();
+    assert!(matches!(lazy.expand(ROOT_ID), Err(LazyErr::Bytes(_))));
+    assert!(!lazy.is_expanded(ROOT_ID));
+    assert_eq!(lazy.len(), 1);
+    assert!(matches!(lazy.expand(ROOT_ID), Err(LazyErr::Bytes(_))));
+    assert_eq!(lazy.child_count(ROOT_ID).unwrap(), 0);
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn from_bytes_depth() {
+    let mut tree = Tree::new_with_root(0i32);
+    let first = tree.new_node(1, LastChild, ROOT_ID).unwrap();
+    let deep = tree.new_node(10, LastChild, first).unwrap();
+    tree.new_node(100, LastChild, deep).unwrap();
+    tree.new_node(2, LastChild, ROOT_ID).unwrap();
+
+    let mut bytes: Vec<u8> = tree.into_bytes().collect();
+    bytes.push(42);
+
+    let mut iter = bytes.iter().copied();
+    let top = Tree::<i32>::from_bytes_depth(&mut iter, 1).unwrap();
+    assert_eq!(iter.next(), Some(42));
+    assert!(tree_matches(&top, vec![(0, 2), (1, 0), (2, 0)]));
+
+    let root_only = Tree::<i32>::from_bytes_depth(&mut bytes.iter().copied(), 0).unwrap();
+    assert!(tree_matches(&root_only, vec![(0, 0)]));
+
+    let full = Tree::<i32>::from_bytes_depth(&mut bytes.iter().copied(), 10).unwrap();
+    assert!(full == tree);
+
+    assert!(Tree::<i32>::from_bytes_depth(&mut bytes[..bytes.len() - 3].iter().copied(), 0).is_err());
+
+    //a skipped child count too big to add to the children left errors instead of overflowing.
+    let mut overflowing = vec![3, 0, 0, 0, 0, 2, 1, 0, 0, 0];
+    overflowing.extend([0xff; 9].iter().chain(&[0x01]));
+    assert!(Tree::<i32>::from_bytes_depth(&mut overflowing.iter().copied(), 0).is_err());
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn byte_len() {
+    assert_eq!(Tree::<i32>::new().byte_len(|_| 4), Tree::<i32>::new().into_bytes().count());
+
+    let mut tree = Tree::new_with_root(ROOT_STR.to_string());
+    let child = tree.new_node(FIRST_ROOT_CHILD_STR.to_string(), LastChild, ROOT_ID).unwrap();
+    tree.new_nodes((0..200).map(|i| i.to_string()), LastChild, child).unwrap();
+
+    assert_eq!(tree.byte_len(|data| 4 + data.len()), tree.into_bytes().count());
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn read_limits() {
+    let mut tree = Tree::new_with_root(0i32);
+    let child = tree.new_node(1, LastChild, ROOT_ID).unwrap();
+    tree.new_nodes(10..13, LastChild, child).unwrap();
+    tree.new_node(2, LastChild, ROOT_ID).unwrap();
+
+    let bytes: Vec<u8> = tree.into_bytes().collect();
+
+    let read = Tree::<i32>::from_bytes_limited(&mut bytes.iter().copied(), &ReadLimits::default()).unwrap();
+    assert!(read == tree);
+
+    let limits = ReadLimits { max_nodes: Some(6), max_depth: Some(2) };
+    assert!(Tree::<i32>::from_bytes_limited(&mut bytes.iter().copied(), &limits).unwrap() == tree);
+
+    let limits = ReadLimits { max_nodes: Some(5), max_depth: None };
+    assert!(matches!(Tree::<i32>::from_bytes_limited(&mut bytes.iter().copied(), &limits), Err(LimitErr::TooManyNodes)));
+
+    let limits = ReadLimits { max_nodes: None, max_depth: Some(1) };
+    assert!(matches!(Tree::<i32>::from_io_bytes_limited(&mut bytes.iter().map(|&byte| Ok(byte)), &limits), Err(LimitErr::TooDeep)));
+
+    //a long chain of single children stops at the depth limit instead of recursing all the way down.
+    let mut deep = vec![1];
+    for _ in 0..100_000 {
+        deep.extend_from_slice(&[0, 1]);
+    }
+    let limits = ReadLimits { max_nodes: None, max_depth: Some(100) };
+    assert!(matches!(Tree::<u8>::from_bytes_limited(&mut deep.into_iter(), &limits), Err(LimitErr::TooDeep)));
+
+    //a claimed child count far bigger than the bytes is an error once the bytes run out.
+    let bytes = vec![1, 0, 0x80, 0x80, 0x80, 0x80, 0x10, 0, 0];
+    assert!(matches!(Tree::<u8>::from_bytes_limited(&mut bytes.into_iter(), &ReadLimits::default()), Err(LimitErr::Bytes(_))));
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn node_count_header() {
+    let mut tree = Tree::new_with_root(0u16);
+    tree.new_nodes(1..300, LastChild, ROOT_ID).unwrap();
+
+    let bytes: Vec<u8> = tree.into_bytes().collect();
+    assert_eq!(&bytes[..2], [0xac, 0x02]);
+    assert!(Tree::<u16>::from_bytes(&mut bytes.into_iter()).unwrap() == tree);
+
+    assert_eq!(Tree::<u16>::new().into_bytes().collect::<Vec<u8>>(), [0]);
+
+    //a header claiming far more nodes than there are bytes doesn't make room for all of them, and fails once the tree ends early.
+    let bytes = vec![0xff, 0xff, 0xff, 0xff, 0x0f, 1, 0, 0];
+    assert!(Tree::<u16>::from_bytes(&mut bytes.into_iter()).is_err());
+
+    //the header has to match the nodes after it either way.
+    let mut bytes: Vec<u8> = Tree::new_with_root(1u8).into_bytes().collect();
+    assert_eq!(bytes, [1, 1, 0]);
+    bytes[0] = 2;
+    assert!(Tree::<u8>::from_bytes(&mut bytes.clone().into_iter()).is_err());
+    assert!(Tree::<u8>::from_io_bytes(&mut bytes.into_iter().map(Ok)).is_err());
+
+    let mut bytes: Vec<u8> = tree.into_bytes().collect();
+    bytes[0] = 0xab;
+    assert!(Tree::<u16>::from_bytes(&mut bytes.clone().into_iter()).is_err());
+    let mut bytes = tree.into_offset_bytes();
+    bytes[0] = 0xab;
+    assert!(Tree::<u16>::from_offset_bytes(&mut bytes.into_iter()).is_err());
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn offset_bytes() {
+    let mut tree = Tree::new_with_root(ROOT_STR.to_string());
+    let first = tree.new_node(FIRST_ROOT_CHILD_STR.to_string(), LastChild, ROOT_ID).unwrap();
+    tree.new_nodes((0..200).map(|i| i.to_string()), LastChild, first).unwrap();
+    let last = tree.new_node(LAST_ROOT_CHILD_STR.to_string(), LastChild, ROOT_ID).unwrap();
+    tree.new_node("deep".to_string(), LastChild, last).unwrap();
+
+    let bytes = tree.into_offset_bytes();
+    assert!(Tree::<String>::from_offset_bytes(&mut bytes.iter().copied()).unwrap() == tree);
+
+    assert_eq!(Tree::<String>::read_at_path(&bytes, &[]).unwrap().unwrap(), ROOT_STR);
+    assert_eq!(Tree::<String>::read_at_path(&bytes, &[0, 150]).unwrap().unwrap(), "150");
+    assert_eq!(Tree::<String>::read_at_path(&bytes, &[1, 0]).unwrap().unwrap(), "deep");
+    assert!(Tree::<String>::read_at_path(&bytes, &[2]).unwrap().is_none());
+    assert!(Tree::<String>::read_at_path(&bytes[..bytes.len() - 5], &[1, 0]).is_err());
+
+    let mut lazy = LazyTree::<String>::with_offsets(&bytes).unwrap();
+    let children = lazy.expand(ROOT_ID).unwrap();
+    assert_eq!(lazy.data_at(children[1]).unwrap(), LAST_ROOT_CHILD_STR);
+    assert!(lazy.into_tree().unwrap() == tree);
+
+    let empty = Tree::<String>::new().into_offset_bytes();
+    assert!(Tree::<String>::read_at_path(&empty, &[]).unwrap().is_none());
+    assert_eq!(Tree::<String>::from_offset_bytes(&mut empty.into_iter()).unwrap().len(), 0);
+}
+
+#[test]
+fn tree_delta() {
+    fn make_changed() -> Tree<&'static str> {
+        let mut tree = make_tree();
+        let new = tree.new_node("new", LastChild, FIRST_ROOT_CHILD_ID).unwrap();
+        tree.new_node("newer", FirstChild, new).unwrap();
+        tree
+    }
+
+    let mut base = make_changed();
+    let mut changed = make_changed();
+
+    assert!(base.delta_to(&changed).is_empty());
+
+    changed.remove(LAST_ROOT_CHILD_ID).unwrap();
+    let added = changed.new_node("added", FirstChild, ROOT_ID).unwrap();
+    *changed.data_at_mut(FIRST_ROOT_CHILD_ID).unwrap() = "edited";
+    let new = changed.children_of(FIRST_ROOT_CHILD_ID).unwrap()[0];
+    changed.move_to(new, LastChild, ROOT_ID).unwrap();
+
+    let delta = base.delta_to(&changed);
+    assert!(!delta.is_empty());
+
+    base.apply_delta(delta).unwrap();
+    assert!(base == changed);
+    assert_eq!(base.data_at(added).unwrap(), &"added");
+    assert_eq!(base.children_of(ROOT_ID).unwrap(), changed.children_of(ROOT_ID).unwrap());
+
+    //a delta made for a different tree is turned down and leaves the tree alone.
+    let mut other = make_tree();
+    let delta = make_changed().delta_to(&changed);
+    assert!(matches!(other.apply_delta(delta), Err(TreeErr::InvalidId)));
+    assert!(other == make_tree());
+
+    let mut empty = make_tree();
+    empty.apply_delta(make_tree().delta_to(&Tree::new())).unwrap();
+    assert_eq!(empty.len(), 0);
+
+    //a removed node whose id is taken again by a new node in the same place.
+    let mut base = make_tree();
+    let mut changed = make_tree();
+    changed.remove(LAST_ROOT_CHILD_ID).unwrap();
+    assert_eq!(changed.new_node("again", LastChild, ROOT_ID).unwrap(), LAST_ROOT_CHILD_ID);
+    changed.new_node("under", LastChild, LAST_ROOT_CHILD_ID).unwrap();
+
+    let delta = base.delta_to(&changed);
+    base.apply_delta(delta).unwrap();
+    assert!(base == changed);
+    assert_eq!(base.subtree_size(ROOT_ID).unwrap(), 4);
+    assert_eq!(base.depth_of(3).unwrap(), 2);
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn tree_delta_bytes() {
+    let base = Tree::new_with_root(0u32);
+    let mut changed = Tree::new_with_root(0u32);
+    changed.new_nodes(1..100, LastChild, ROOT_ID).unwrap();
+
+    let delta = base.delta_to(&changed);
+    let bytes: Vec<u8> = delta.into_bytes().collect();
+    let read = TreeDelta::<u32>::from_bytes(&mut bytes.into_iter()).unwrap();
+    assert!(read == delta);
+
+    let mut base = base;
+    base.apply_delta(read).unwrap();
+    assert!(base == changed);
+
+    let small = Tree::new_with_root(0u32).delta_to(&Tree::new_with_root(1u32));
+    assert!(small.into_bytes().count() < 10);
+
+    //a delta adding a node with an id far past the tree is turned down without changing the tree.
+    let mut far: Vec<u8> = vec![1, 0, 1];
+    far.extend([0xff; 9].iter().chain(&[0x01]));
+    far.extend(&[7, 0, 0, 0, 0]);
+    let far = TreeDelta::<u32>::from_bytes(&mut far.into_iter()).unwrap();
+    assert!(matches!(base.apply_delta(far), Err(TreeErr::OutOfRange)));
+    assert!(base == changed);
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn subtree_into_bytes() {
+    let mut tree = Tree::new_with_root(0i32);
+    let branch = tree.new_node(1, LastChild, ROOT_ID).unwrap();
+    let child = tree.new_node(10, LastChild, branch).unwrap();
+    tree.new_nodes(100..103, LastChild, child).unwrap();
+    tree.new_node(2, LastChild, ROOT_ID).unwrap();
+
+    let bytes: Vec<u8> = tree.subtree_into_bytes(branch).unwrap().collect();
+    let copy = Tree::<i32>::from_bytes(&mut bytes.into_iter()).unwrap();
+    assert!(tree_matches(&copy, vec![(1, 1), (10, 3), (100, 0), (101, 0), (102, 0)]));
+
+    let whole: Vec<u8> = tree.subtree_into_bytes(ROOT_ID).unwrap().collect();
+    assert_eq!(whole, tree.into_bytes().collect::<Vec<u8>>());
+
+    assert!(matches!(tree.subtree_into_bytes(100), Err(TreeErr::InvalidId)));
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn attach_from_bytes() {
+    let mut branch = Tree::new_with_root(10i32);
+    branch.new_nodes(100..103, LastChild, ROOT_ID).unwrap();
+    let bytes: Vec<u8> = branch.into_bytes().collect();
+
+    let mut tree = Tree::new_with_root(0i32);
+    let first = tree.new_node(1, LastChild, ROOT_ID).unwrap();
+    tree.new_node(2, LastChild, ROOT_ID).unwrap();
+
+    let new = tree.attach_from_bytes(&mut bytes.iter().copied(), SiblingAfter, first).unwrap();
+    assert_eq!(tree.children_of(ROOT_ID).unwrap()[1], new);
+    assert!(tree_matches(&tree, vec![(0, 3), (1, 0), (10, 3), (100, 0), (101, 0), (102, 0), (2, 0)]));
+
+    let new = tree.attach_from_io_bytes(&mut bytes.iter().map(|&byte| Ok(byte)), FirstChild, first).unwrap();
+    assert_eq!(tree.parent_of(new).unwrap(), Some(first));
+    assert_eq!(tree.len(), 11);
+
+    assert!(matches!(tree.attach_from_bytes(&mut bytes[..bytes.len() - 2].iter().copied(), LastChild, first), Err(AttachErr::Bytes(_))));
+    assert_eq!(tree.len(), 11);
+    assert!(matches!(tree.attach_from_bytes(&mut bytes.iter().copied(), SiblingAfter, ROOT_ID), Err(AttachErr::Tree(TreeErr::CantBeRoot))));
+    assert!(matches!(tree.attach_from_bytes(&mut vec![0u8].into_iter(), LastChild, first), Err(AttachErr::Tree(TreeErr::NoRoot))));
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn dedup_bytes() {
+    let mut tree = Tree::new_with_root("folder".to_string());
+    for i in 0..50 {
+        let child = tree.new_node(if i % 2 == 0 { "folder" } else { "file" }.to_string(), LastChild, ROOT_ID).unwrap();
+        tree.new_node("file".to_string(), LastChild, child).unwrap();
+    }
+
+    let bytes = tree.into_dedup_bytes();
+    assert!(bytes.len() * 3 < tree.into_bytes().count());
+
+    assert!(Tree::<String>::from_dedup_bytes(&mut bytes.iter().copied()).unwrap() == tree);
+    assert!(Tree::<String>::from_dedup_io_bytes(&mut bytes.iter().map(|&byte| Ok(byte))).unwrap() == tree);
+
+    let empty = Tree::<String>::new().into_dedup_bytes();
+    assert_eq!(Tree::<String>::from_dedup_bytes(&mut empty.into_iter()).unwrap().len(), 0);
+
+    //one node using the second entry of a one entry table.
+    let bytes = vec![1, 1, 7, 1, 0];
+    assert!(matches!(Tree::<u8>::from_dedup_bytes(&mut bytes.into_iter()), Err(DedupErr::UnknownData)));
+    assert!(matches!(Tree::<u8>::from_dedup_bytes(&mut vec![1, 1].into_iter()), Err(DedupErr::Bytes(_))));
+}
+
+#[test]
+fn deep_traversals() {
+    let mut tree = Tree::new_with_root(0);
+    let mut parent = ROOT_ID;
+    for i in 1..10_000 {
+        parent = tree.new_node(i, LastChild, parent).unwrap();
+    }
+
+    //a small stack is enough since none of the traversals recurse.
+    let handle = std::thread::Builder::new().stack_size(64 * 1024).spawn(move || {
+        assert_eq!(tree.descendants_of(ROOT_ID).unwrap().len(), 9_999);
+        assert_eq!(tree.sub_tree(ROOT_ID).unwrap().len(), 10_000);
+
+        let info = tree.sub_tree_info(ROOT_ID).unwrap();
+        assert_eq!(info.len(), 10_000);
+        assert_eq!(info[9_999].depth, 9_999);
+        assert_eq!(info[9_999].child_count, 0);
+
+        assert_eq!(tree.sub_tree_depth(ROOT_ID, 5).unwrap().len(), 6);
+        let info = tree.sub_tree_depth_info(ROOT_ID, 5).unwrap();
+        assert_eq!(info.len(), 6);
+        assert_eq!(info[5].child_count, 1);
+    }).unwrap();
+
+    handle.join().unwrap();
+}
+
+#[test]
+fn deep_clone_to() {
+    let mut tree = Tree::new_with_root(String::from("root"));
+    let mut parent = ROOT_ID;
+    for i in 1..5_000 {
+        parent = tree.new_node(i.to_string(), LastChild, parent).unwrap();
+    }
+    let first = tree.first_child_of(ROOT_ID).unwrap().unwrap();
+
+    let handle = std::thread::Builder::new().stack_size(64 * 1024).spawn(move || {
+        let copy = tree.clone_to(first, LastChild, ROOT_ID).unwrap();
+        assert_eq!(tree.len(), 5_000 + 4_999);
+        assert_eq!(tree.subtree_size(copy).unwrap(), 4_999);
+        assert_eq!(tree.sub_tree(copy).unwrap().iter().map(|&id| tree.data_at(id).unwrap().clone()).collect::<Vec<_>>(), (1..5_000).map(|i| i.to_string()).collect::<Vec<_>>());
+    }).unwrap();
+
+    handle.join().unwrap();
+}
+
+#[test]
+fn deep_remove() {
+    let mut tree = Tree::new_with_root(0);
+    let mut parent = ROOT_ID;
+    for i in 1..10_000 {
+        parent = tree.new_node(i, LastChild, parent).unwrap();
+    }
+    let first = tree.first_child_of(ROOT_ID).unwrap().unwrap();
+    let wide = tree.new_node(-1, LastChild, ROOT_ID).unwrap();
+    tree.new_nodes(0..100, LastChild, wide).unwrap();
+
+    let handle = std::thread::Builder::new().stack_size(64 * 1024).spawn(move || {
+        tree.remove(first).unwrap();
+        assert_eq!(tree.len(), 102);
+        assert_eq!(tree.subtree_size(ROOT_ID).unwrap(), 102);
+
+        tree.remove(wide).unwrap();
+        assert!(tree_matches(&tree, vec![(0, 0)]));
+
+        //every freed slot can be used again.
+        let ids = tree.new_nodes(0..10_100, LastChild, ROOT_ID).unwrap();
+        assert!(ids.iter().all(|&id| id < 10_101));
+    }).unwrap();
+
+    handle.join().unwrap();
+}
+
+#[cfg(feature = "bytebuffer")]
+#[test]
+fn deep_from_bytes() {
+    let mut tree = Tree::new_with_root(0u8);
+    let mut parent = ROOT_ID;
+    for i in 1..5_000 {
+        parent = tree.new_node((i % 256) as u8, LastChild, parent).unwrap();
+    }
+    let bytes: Vec<u8> = tree.into_bytes().collect();
+    let offset_bytes = tree.into_offset_bytes();
+    let dedup_bytes = tree.into_dedup_bytes();
+
+    let handle = std::thread::Builder::new().stack_size(64 * 1024).spawn(move || {
+        assert!(Tree::<u8>::from_bytes(&mut bytes.iter().copied()).unwrap() == tree);
+        assert!(Tree::<u8>::from_io_bytes(&mut bytes.iter().map(|&byte| Ok(byte))).unwrap() == tree);
+        assert_eq!(Tree::<u8>::from_bytes_depth(&mut bytes.iter().copied(), 2_500).unwrap().len(), 2_501);
+        assert!(Tree::<u8>::from_offset_bytes(&mut offset_bytes.into_iter()).unwrap() == tree);
+        assert!(Tree::<u8>::from_dedup_bytes(&mut dedup_bytes.into_iter()).unwrap() == tree);
+    }).unwrap();
+
+    handle.join().unwrap();
+}
+
+#[test]
+fn move_into_own_sub_tree() {
+    let mut tree = Tree::new_with_root(0);
+    let mut parent = ROOT_ID;
+    for i in 1..1_000 {
+        parent = tree.new_node(i, LastChild, parent).unwrap();
+    }
+    let first = tree.first_child_of(ROOT_ID).unwrap().unwrap();
+
+    assert!(matches!(tree.move_to(first, LastChild, parent), Err(TreeErr::CantMoveIntoChild)));
+    assert!(matches!(tree.move_to(first, SiblingAfter, first), Err(TreeErr::CantMoveIntoChild)));
+    assert!(matches!(tree.move_to(ROOT_ID, LastChild, first), Err(TreeErr::CantMoveIntoChild)));
+
+    tree.move_to(parent, LastChild, ROOT_ID).unwrap();
+    assert_eq!(tree.parent_of(parent).unwrap(), Some(ROOT_ID));
+    assert_eq!(tree.subtree_size(first).unwrap(), 998);
+}
+
+#[test]
+fn packed_data() {
+    let mut tree = Tree::new_with_root(0);
+    let ids = tree.new_nodes(1..10, LastChild, ROOT_ID).unwrap();
+    tree.new_nodes(10..13, LastChild, ids[2]).unwrap();
+
+    tree.remove(ids[2]).unwrap();
+    tree.remove(ids[0]).unwrap();
+    for (i, &id) in ids.iter().enumerate().filter(|&(i, _)| i != 0 && i != 2) {
+        assert_eq!(*tree.data_at(id).unwrap(), i as i32 + 1);
+    }
+
+    let new = tree.new_node(20, FirstChild, ids[8]).unwrap();
+    *tree.data_at_mut(ids[1]).unwrap() = 30;
+
+    let doubled = tree.map(|data| data * 2);
+    assert_eq!(*doubled.data_at(new).unwrap(), 40);
+    assert_eq!(*doubled.data_at(ids[1]).unwrap(), 60);
+    assert_eq!(*doubled.data_at(ids[8]).unwrap(), 18);
+    assert!(doubled.data_at(ids[2]).is_err());
+}
+
+#[test]
+fn capacity() {
+    let mut tree = Tree::with_capacity(100);
+    assert!(tree.capacity() >= 100);
+    assert_eq!(tree.len(), 0);
+
+    tree.new_root(0);
+    let ids = tree.new_nodes(1..100, LastChild, ROOT_ID).unwrap();
+    assert!(tree.capacity() >= 100);
+
+    assert_eq!(tree.free_count(), 0);
+    let full_usage = tree.memory_usage();
+    assert!(full_usage >= 100 * std::mem::size_of::<i32>());
+
+    for &id in &ids[..90] {
+        tree.remove(id).unwrap();
+    }
+    assert_eq!(tree.free_count(), 90);
+    tree.shrink_to_fit();
+    assert!(tree.memory_usage() < full_usage);
+    assert!(tree.capacity() >= tree.len());
+    assert_eq!(*tree.data_at(ids[95]).unwrap(), 96);
+
+    tree.reserve(1000);
+    assert!(tree.capacity() >= tree.len() + 1000);
+    tree.try_reserve(2000).unwrap();
+    assert!(tree.capacity() >= tree.len() + 2000);
+    assert!(tree.try_reserve(usize::MAX).is_err());
+}
+
+#[test]
+fn free_count() {
+    let mut tree = make_tree();
+    let ids = tree.new_nodes(vec!["a", "b", "c", "d"], LastChild, LAST_ROOT_CHILD_ID).unwrap();
+    assert_eq!(tree.free_count(), 0);
+
+    tree.remove(ids[0]).unwrap();
+    assert_eq!(tree.free_count(), 1);
+    tree.remove(LAST_ROOT_CHILD_ID).unwrap();
+    assert_eq!(tree.free_count(), 5);
+
+    //new nodes take the freed slots before the tree grows.
+    let slots = tree.capacity();
+    tree.new_nodes(vec!["e", "f", "g"], LastChild, ROOT_ID).unwrap();
+    assert_eq!(tree.free_count(), 2);
+    tree.new_nodes(vec!["h", "i", "j"], LastChild, ROOT_ID).unwrap();
+    assert_eq!(tree.free_count(), 0);
+    assert_eq!(tree.len(), 8);
+    assert!(tree.capacity() >= slots);
+}
+
+#[test]
+fn memory_usage_grows() {
+    let mut tree = Tree::new_with_root(0);
+    let mut usage = tree.memory_usage();
+
+    for i in 1..200 {
+        tree.new_node(i, LastChild, ROOT_ID).unwrap();
+        let next = tree.memory_usage();
+        assert!(next >= usage);
+        usage = next;
+    }
+    assert!(usage >= 200 * std::mem::size_of::<i32>());
+}
+
+//counts the bytes it has handed out and not yet been given back.
+#[cfg(feature = "allocator_api")]
+#[derive(Clone, Copy)]
+struct Counting<'a>(&'a std::cell::Cell<usize>);
+
+#[cfg(feature = "allocator_api")]
+unsafe impl std::alloc::Allocator for Counting<'_> {
+    fn allocate(&self, layout: std::alloc::Layout) -> Result<std::ptr::NonNull<[u8]>, std::alloc::AllocError> {
+        self.0.set(self.0.get() + layout.size());
+        std::alloc::Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: std::ptr::NonNull<u8>, layout: std::alloc::Layout) {
+        self.0.set(self.0.get() - layout.size());
+        unsafe { std::alloc::Global.deallocate(ptr, layout) }
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+#[test]
+fn custom_allocator() {
+    let used = std::cell::Cell::new(0);
+    {
+        let mut tree = Tree::new_in(Counting(&used));
+        assert_eq!(used.get(), 0);
+
+        let root = tree.new_root(0);
+        let ids = tree.new_nodes(1..10, LastChild, root).unwrap();
+        tree.new_nodes(10..13, LastChild, ids[4]).unwrap();
+        assert!(used.get() >= 13 * std::mem::size_of::<i32>());
+
+        tree.remove(ids[1]).unwrap();
+        tree.move_to(ids[4], FirstChild, ids[0]).unwrap();
+        assert_eq!(tree.len(), 12);
+        assert_eq!(tree.subtree_size(ids[0]).unwrap(), 5);
+        assert_eq!(tree.fold(root, |&data, children: Vec<i32>| data + children.iter().sum::<i32>()).unwrap(), 76);
+
+        let usage = used.get();
+        tree.compact();
+        assert!(used.get() <= usage);
+        let moved = tree.first_child_of(tree.first_child_of(root).unwrap().unwrap()).unwrap().unwrap();
+        assert_eq!(*tree.data_at(moved).unwrap(), 5);
+
+        let copy = tree.map(|&data| data);
+        assert_eq!(copy.len(), 12);
+
+        let tree: Tree<i32, Counting> = Tree::with_capacity_in(50, *tree.allocator());
+        assert!(tree.capacity() >= 50);
+    }
+    assert_eq!(used.get(), 0);
+}
+
+#[test]
+fn compact() {
+    let mut tree = Tree::new_with_root(0);
+    let ids = tree.new_nodes(1..10, LastChild, ROOT_ID).unwrap();
+    let grand_children = tree.new_nodes(10..13, LastChild, ids[4]).unwrap();
+    tree.remove(ids[1]).unwrap();
+    tree.remove(ids[6]).unwrap();
+
+    let before = tree.map(|&data| data);
+    let remap = tree.compact();
+    assert_eq!(tree.len(), 11);
+    assert_eq!(remap[ids[1]], None);
+    assert_eq!(remap[ids[6]], None);
+    assert_eq!(remap[ROOT_ID], Some(0));
+    assert!(remap.iter().flatten().all(|&id| id < 11));
+
+    assert_eq!(*tree.data_at(remap[grand_children[2]].unwrap()).unwrap(), 12);
+    assert_eq!(tree.parent_of(remap[grand_children[0]].unwrap()).unwrap(), remap[ids[4]]);
+    assert!(Tree::structural_eq(&tree, &before));
+
+    //new nodes go on the end since there are no free slots left.
+    assert_eq!(tree.free_count(), 0);
+    assert_eq!(tree.new_node(20, LastChild, 0).unwrap(), 11);
+}
+
+#[test]
+fn reorder_dfs() {
+    let mut tree = Tree::new_with_root(0);
+    let ids = tree.new_nodes(1..4, LastChild, ROOT_ID).unwrap();
+    tree.new_nodes(4..6, FirstChild, ids[0]).unwrap();
+    tree.new_node(6, LastChild, ids[2]).unwrap();
+    tree.remove(ids[1]).unwrap();
+
+    let before = tree.map(|&data| data);
+    let remap = tree.reorder_dfs();
+    assert_eq!(remap[ids[1]], None);
+    assert!(Tree::structural_eq(&tree, &before));
+
+    //ids now count up in the same order as sub_tree.
+    assert_eq!(tree.sub_tree(ROOT_ID).unwrap(), (0..6).collect::<Vec<_>>());
+    let data: Vec<i32> = (0..6).map(|id| *tree.data_at(id).unwrap()).collect();
+    assert_eq!(data, vec![0, 1, 4, 5, 3, 6]);
+
+    let mut empty = Tree::<i32>::new();
+    assert!(empty.reorder_dfs().is_empty());
+}
+
+#[test]
+fn child_count() {
+    let mut tree = Tree::new_with_root(0);
+    let ids = tree.new_nodes(1..5, LastChild, ROOT_ID).unwrap();
+    assert_eq!(tree.child_count(ROOT_ID).unwrap(), 4);
+    assert_eq!(tree.child_count(ids[0]).unwrap(), 0);
+
+    tree.new_node(5, FirstChild, ids[0]).unwrap();
+    tree.new_node(6, SiblingBefore, ids[1]).unwrap();
+    tree.new_node(7, SiblingAfter, ids[3]).unwrap();
+    assert_eq!(tree.child_count(ROOT_ID).unwrap(), 6);
+    assert_eq!(tree.child_count(ids[0]).unwrap(), 1);
+
+    tree.move_to(ids[1], LastChild, ids[0]).unwrap();
+    tree.remove(ids[2]).unwrap();
+    assert_eq!(tree.child_count(ROOT_ID).unwrap(), 4);
+    assert_eq!(tree.child_count(ids[0]).unwrap(), 2);
+
+    tree.group_children(ROOT_ID, 1..3, 8).unwrap();
+    tree.flatten(ROOT_ID).unwrap();
+    tree.reroot(ids[3]).unwrap();
+    for id in tree.sub_tree(ids[3]).unwrap() {
+        assert_eq!(tree.child_count(id).unwrap(), tree.children_of(id).unwrap().len());
+    }
+    assert!(tree.child_count(100).is_err());
+}
+
+#[test]
+fn depth_of() {
+    let mut tree = Tree::new_with_root(0);
+    let ids = tree.new_nodes(1..4, LastChild, ROOT_ID).unwrap();
+    let mut parent = ids[0];
+    for i in 4..8 {
+        parent = tree.new_node(i, LastChild, parent).unwrap();
+    }
+    assert_eq!(tree.depth_of(ROOT_ID).unwrap(), 0);
+    assert_eq!(tree.depth_of(ids[2]).unwrap(), 1);
+    assert_eq!(tree.depth_of(parent).unwrap(), 5);
+
+    fn check(tree: &Tree<i32>) {
+        for node in tree.sub_tree_info(tree.get_root().unwrap()).unwrap() {
+            assert_eq!(tree.depth_of(node.id).unwrap(), node.depth);
+        }
+    }
+
+    tree.move_to(ids[0], LastChild, ids[2]).unwrap();
+    assert_eq!(tree.depth_of(parent).unwrap(), 6);
+    check(&tree);
+
+    tree.promote(ids[2]).unwrap();
+    check(&tree);
+    tree.reroot(parent).unwrap();
+    check(&tree);
+    tree.flatten(ids[1]).unwrap();
+    check(&tree);
+    tree.make_root(ids[0]).unwrap();
+    assert_eq!(tree.depth_of(ids[0]).unwrap(), 0);
+    check(&tree);
+
+    let mut forest = Forest::new();
+    let root = forest.add_root(0);
+    let child = forest.new_node(1, LastChild, root).unwrap();
+    assert_eq!(forest.depth_of(root).unwrap(), 0);
+    assert_eq!(forest.depth_of(child).unwrap(), 1);
+}
+
+#[cfg(feature = "subtree_sizes")]
+#[test]
+fn rank() {
+    let mut tree = Tree::new_with_root(0);
+    let ids = tree.new_nodes(1..4, LastChild, ROOT_ID).unwrap();
+    tree.new_nodes(4..7, LastChild, ids[0]).unwrap();
+    tree.new_nodes(7..9, FirstChild, ids[2]).unwrap();
+    tree.move_to(ids[1], LastChild, ids[2]).unwrap();
+
+    let order = tree.sub_tree(ROOT_ID).unwrap();
+    for (rank, &id) in order.iter().enumerate() {
+        assert_eq!(tree.rank_of(id).unwrap(), rank);
+        assert_eq!(tree.node_at_rank(rank).unwrap(), id);
+    }
+
+    assert!(matches!(tree.node_at_rank(order.len()), Err(TreeErr::OutOfRange)));
+    assert!(matches!(Tree::<i32>::new().node_at_rank(0), Err(TreeErr::OutOfRange)));
+    assert!(tree.rank_of(100).is_err());
+}
+
+#[test]
+fn data_at_many_mut() {
+    let mut tree = Tree::new_with_root(10);
+    let child = tree.new_node(2, LastChild, ROOT_ID).unwrap();
+    let other = tree.new_node(5, LastChild, ROOT_ID).unwrap();
+
+    let [parent_data, child_data] = tree.data_at_many_mut([ROOT_ID, child]).unwrap();
+    *parent_data -= 4;
+    *child_data += 4;
+    assert_eq!(*tree.data_at(ROOT_ID).unwrap(), 6);
+    assert_eq!(*tree.data_at(child).unwrap(), 6);
+
+    let [a, b, c] = tree.data_at_many_mut([other, ROOT_ID, child]).unwrap();
+    std::mem::swap(a, b);
+    *c = 0;
+    assert_eq!(*tree.data_at(other).unwrap(), 6);
+    assert_eq!(*tree.data_at(ROOT_ID).unwrap(), 5);
+
+    assert!(matches!(tree.data_at_many_mut([child, child]), Err(TreeErr::OverlappingIds)));
+    tree.remove(other).unwrap();
+    assert!(matches!(tree.data_at_many_mut([child, other]), Err(TreeErr::InvalidId)));
+    assert!(matches!(tree.data_at_many_mut([100]), Err(TreeErr::InvalidId)));
+    assert!(tree.data_at_many_mut([]).unwrap().is_empty());
+}
+
+#[test]
+fn remove_many() {
+    let mut tree = Tree::new_with_root(0);
+    let ids = tree.new_nodes(1..5, LastChild, ROOT_ID).unwrap();
+    let grand_children = tree.new_nodes(5..8, LastChild, ids[1]).unwrap();
+
+    tree.remove_many(&[grand_children[0], ids[1], ids[3], ids[1], grand_children[2]]).unwrap();
+    assert!(tree_matches(&tree, vec![(0, 2), (1, 0), (3, 0)]));
+    assert_eq!(tree.free_count(), 5);
+
+    assert!(matches!(tree.remove_many(&[ids[0], ids[1]]), Err(TreeErr::InvalidId)));
+    assert!(tree.contains(ids[0]));
+
+    tree.remove_many(&[ids[2], ROOT_ID]).unwrap();
+    assert_eq!(tree.len(), 0);
+    assert_eq!(tree.get_root(), None);
+}
+
+#[test]
+fn deep_chain() {
+    const DEPTH: usize = 20_000;
+
+    let mut tree = Tree::new_with_root(0);
+    let mut last = ROOT_ID;
+    for i in 1..DEPTH {
+        last = tree.new_node(i, LastChild, last).unwrap();
+    }
+
+    let levels = tree.fold(ROOT_ID, |_, children: Vec<usize>| children.first().map_or(1, |levels| levels + 1)).unwrap();
+    assert_eq!(levels, DEPTH);
+    assert_eq!(tree.to_nested_set()[0], (1, DEPTH * 2, &0));
+    assert!(tree.to_html(ROOT_ID, |data| *data).unwrap().ends_with("</li></ul></li></ul>"));
+
+    let middle = tree.find(ROOT_ID, |&data| data == DEPTH / 2).unwrap().unwrap();
+    let lower = tree.extract_subtrees(&[middle]).unwrap().pop().unwrap();
+    assert_eq!(tree.len(), DEPTH / 2);
+    assert_eq!(lower.len(), DEPTH / 2);
+    assert_eq!(lower.height_of(lower.get_root().unwrap()).unwrap(), DEPTH / 2 - 1);
+    assert_eq!(lower.fold(lower.get_root().unwrap(), |&data, children: Vec<usize>| children.first().map_or(data, |&last| last)).unwrap(), DEPTH - 1);
+}
@@ -1,45 +1,255 @@
 //! Tree is a generic collection type that allows to crawling around using the relations of nodes or 
 //! jumping to specific nodes with ids.
 //! 
-//! Implements all bytebuffer traits.
+//! Implements all bytebuffer traits with the bytebuffer feature, which is on by default, and the serde traits with the serde feature.
+//! With the u32_index feature the links between nodes are stored as u32 instead of usize, halving their size for trees with
+//! fewer than u32::MAX nodes.
+//! The subtree_sizes feature, which is on by default, keeps the size of every sub tree up to date as the tree changes, so subtree_size,
+//! rank_of and node_at_rank don't have to walk the tree. Without it moving and adding nodes no longer has to update their ancestors.
+//! 
+//! A tree keeps its nodes in a few vectors from the global allocator. With the allocator_api feature, which needs a nightly compiler,
+//! Tree takes the allocator as a second parameter so new_in and with_capacity_in can keep a tree in an arena or pool. Trees in other
+//! allocators have every method that works within one tree, while building trees, moving nodes between trees and the trait impls are
+//! left to trees in the global allocator. Removed nodes leave free slots that new nodes take first, so once a tree has been made with
+//! with_capacity or has had room reserved, new_node, remove and move_to don't allocate until it grows past that room.
 //! 
 //! ## Structs
 //! - Tree
+//! - Forest
+//! - TreeStats
+//! - IndexedTree
+//! - TreeBuilder
+//! - LazyTree (bytebuffer feature)
+//! - ReadLimits (bytebuffer feature)
+//! - NestedNode
+//! - PrettyOptions
+//! - TreeDelta
+//! - FileEntry (fs feature)
+//! - DirOptions (fs feature)
+//! - TreeStrategy (proptest feature)
+//! - TreeValueTree (proptest feature)
+//! - FlatTree (serde feature)
+//! - ArchivedTree (rkyv feature)
+//! - XmlElement (xml feature)
+//! - NewickNode (newick feature)
 //! - NodeChildren
-//! - TreeIter
+//! - TreeIter (bytebuffer feature)
+//! - ChecksumIter (bytebuffer feature)
+//! - CompressedIter (compression feature)
 //! 
 //! ## Enums
 //! - Position
 //! - TreeErr
-
+//! - MergeDecision
+//! - ChecksumErr (bytebuffer feature)
+//! - LazyErr (bytebuffer feature)
+//! - LimitErr (bytebuffer feature)
+//! - AttachErr (bytebuffer feature)
+//! - DedupErr (bytebuffer feature)
+//! - XmlErr (xml feature)
+
+#[cfg(feature = "bytebuffer")]
 use bytebuffer::*;
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "allocator_api")]
+use std::alloc::{Allocator, Global};
+
+//implements the methods for trees in any allocator with the allocator_api feature, or for the only kind of tree there is without it.
+macro_rules! impl_in_any_allocator {
+    ($($item:tt)*) => {
+        #[cfg(feature = "allocator_api")]
+        impl<T, A: Allocator> Tree<T, A> {
+            $($item)*
+        }
+
+        #[cfg(not(feature = "allocator_api"))]
+        impl<T> Tree<T> {
+            $($item)*
+        }
+    };
+}
+
+mod index;
+use index::*;
+
+mod forest;
+pub use forest::*;
+
+#[cfg(feature = "bytebuffer")]
+mod bytes;
+#[cfg(feature = "bytebuffer")]
+pub use bytes::*;
+
+mod indexed;
+pub use indexed::*;
+
+mod builder;
+pub use builder::*;
+
+mod nested;
+pub use nested::*;
+
+mod pretty;
+pub use pretty::*;
+
+mod sexpr;
+
+mod delta;
+pub use delta::*;
+
+#[cfg(feature = "bytebuffer")]
+mod checksum;
+#[cfg(feature = "bytebuffer")]
+pub use checksum::*;
+
+#[cfg(feature = "bytebuffer")]
+mod stream;
+
+#[cfg(feature = "bytebuffer")]
+mod lazy;
+#[cfg(feature = "bytebuffer")]
+pub use lazy::*;
+
+#[cfg(feature = "bytebuffer")]
+mod offsets;
+
+#[cfg(feature = "bytebuffer")]
+mod dedup;
+#[cfg(feature = "bytebuffer")]
+pub use dedup::*;
+
+#[cfg(feature = "bytebuffer")]
+mod limits;
+#[cfg(feature = "bytebuffer")]
+pub use limits::*;
+
+#[cfg(feature = "async")]
+mod async_stream;
+
+#[cfg(feature = "compression")]
+mod compressed;
+#[cfg(feature = "compression")]
+pub use compressed::*;
+
+#[cfg(feature = "fs")]
+mod fs;
+#[cfg(feature = "fs")]
+pub use fs::*;
+
+#[cfg(feature = "petgraph")]
+mod graph;
+
+#[cfg(feature = "ego-tree")]
+mod ego;
+
+#[cfg(feature = "indextree")]
+mod arena;
+
+#[cfg(feature = "testing")]
+mod random;
+
+#[cfg(feature = "proptest")]
+mod arbitrary;
+#[cfg(feature = "proptest")]
+pub use arbitrary::*;
+
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "serde")]
+pub use serialize::*;
+
+#[cfg(feature = "json")]
+mod json;
+
+#[cfg(feature = "rkyv")]
+mod archive;
 
-/// The individual nodes on the tree.
-struct Node<T> {
-    parent: Option<usize>,
+#[cfg(feature = "xml")]
+mod xml;
+#[cfg(feature = "xml")]
+pub use xml::*;
 
-    prev_sib: Option<usize>,
-    next_sib: Option<usize>,
+#[cfg(feature = "newick")]
+mod newick;
+#[cfg(feature = "newick")]
+pub use newick::*;
 
-    first_child: Option<usize>,
-    last_child: Option<usize>,
+/// The links of an individual node on the tree to the nodes around it.
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[derive(Clone, Copy)]
+struct Node {
+    parent: Link,
+
+    prev_sib: Link,
+    next_sib: Link,
 
-    data: Option<T>,
+    first_child: Link,
+    last_child: Link,
+
+    //number of nodes in the sub tree of the node, including itself.
+    #[cfg(feature = "subtree_sizes")]
+    size: Index,
+    child_count: Index,
+    //number of ancestors the node has.
+    depth: Index,
 }
 
-impl<T> Node<T> {
-    pub fn new(data: T) -> Node<T> {
+impl Node {
+    pub fn new() -> Node {
         Node{
-            parent:      None,
-            prev_sib:    None,
-            next_sib:    None,
-            first_child: None,
-            last_child:  None,
-            data:        Some(data),
+            parent:      Link::NONE,
+            prev_sib:    Link::NONE,
+            next_sib:    Link::NONE,
+            first_child: Link::NONE,
+            last_child:  Link::NONE,
+            #[cfg(feature = "subtree_sizes")]
+            size:        1,
+            child_count: 0,
+            depth:       0,
+        }
+    }
+}
+
+/// A place in the tree's storage for a node. Free slots only keep the link to the next free slot.
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[derive(Clone, Copy)]
+enum Slot {
+    //a node along with the index of its data in the tree's data.
+    Occupied(Node, Index),
+    //linked into the tree without any data, like the top of a forest or a node whose data has been taken.
+    Hidden(Node),
+    Free(Link),
+}
+
+impl std::ops::Deref for Slot {
+    type Target = Node;
+
+    fn deref(&self) -> &Node {
+        match self {
+            Slot::Occupied(node, _) | Slot::Hidden(node) => node,
+            Slot::Free(_) => unreachable!("free slots have no links"),
+        }
+    }
+}
+
+impl std::ops::DerefMut for Slot {
+    fn deref_mut(&mut self) -> &mut Node {
+        match self {
+            Slot::Occupied(node, _) | Slot::Hidden(node) => node,
+            Slot::Free(_) => unreachable!("free slots have no links"),
         }
     }
 }
 
+//whether walk_sub_tree is at a node before going through its children or after.
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    Enter,
+    Leave,
+}
+
 /// The id of a node along with the number of children that it has.
 #[derive(Clone, Copy)]
 pub struct NodeInfo{
@@ -48,6 +258,17 @@ pub struct NodeInfo{
     pub depth: usize,
 }
 
+/// A summary of the shape of a tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeStats {
+    pub node_count: usize,
+    pub height: usize,
+    pub leaf_count: usize,
+    pub max_children: usize,
+    /// The number of nodes at each depth starting from the root.
+    pub level_counts: Vec<usize>,
+}
+
 /// The positions that a node can be placed in relation to another node.
 #[derive(PartialEq)]
 pub enum Position {
@@ -65,18 +286,69 @@ pub enum TreeErr{
     InvalidId,
     CantBeRoot,
     CantMoveIntoChild,
+    OverlappingIds,
+    OutOfRange,
+    DuplicateKey,
+    UnknownIndex,
+    MultipleRoots,
+    NoOpenNode,
+    ShapeMismatch,
+    NoRoot,
+    Cycle,
+    InvalidNesting,
+    MultipleParents,
 }
 
 use TreeErr::*;
 
+//the most slots that ids read from outside the tree can take for each node, and the least they can always take.
+const SLOTS_PER_LISTED_NODE: usize = 8;
+const MIN_LISTED_SLOTS: usize = 64;
+
+/// What to do with the descendants of two nodes that are combined while merging trees.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum MergeDecision {
+    /// Keep the children already in the tree, discarding the other node's children.
+    Keep,
+    /// Replace the children already in the tree with the other node's children.
+    Replace,
+    /// Merge the children of both nodes by key.
+    Combine,
+}
+
+use MergeDecision::*;
+
 /// A collection of nodes and there relations.
 /// 
 /// ## Functions
 /// - new
+/// - with_capacity
 /// - new_with_root
+/// - from_nested
+/// - unfold
+/// - zip
+/// - from_parent_list
+/// - from_edges
+/// - from_nested_set
+/// - from_paths
+/// - from_indented
+/// - from_sexpr
 /// 
 /// ## Methods
 /// - len
+/// - capacity
+/// - free_count
+/// - memory_usage
+/// - reserve
+/// - try_reserve
+/// - shrink_to_fit
+/// - compact
+/// - reorder_dfs
+/// - contains
+/// - subtree_size
+/// - rank_of (subtree_sizes feature)
+/// - node_at_rank (subtree_sizes feature)
+/// - stats
 /// - descendants_of
 /// - sub_tree
 /// - sub_tree_info
@@ -85,28 +357,142 @@ use TreeErr::*;
 /// - sub_tree_depth
 /// - sub_tree_depth_info
 /// - children_of
+/// - child_count
+/// - height_of
+/// - depth_of
+/// - is_ancestor_of
+/// - is_descendant_of
+/// - is_leaf
+/// - is_root
+/// - path_to_root
+/// - path_between
+/// - distance
+/// - find
+/// - find_all
+/// - count_where
+/// - fold
+/// - map
+/// - filter_map
+/// - to_parent_list
+/// - to_edges
+/// - to_nested_set
+/// - to_closure_table
+/// - to_indented
+/// - pretty_print
+/// - pretty_print_from
+/// - pretty_print_io
+/// - pretty_print_io_from
+/// - to_html
+/// - to_sexpr
+/// - byte_len (bytebuffer feature)
+/// - is_isomorphic
+/// - is_isomorphic_by
+/// - find_pattern_by
 /// - new_node
+/// - new_nodes
 /// - remove
+/// - remove_many
 /// - data_at
 /// - data_at_mut
+/// - data_at_many_mut
 /// - get_root
 /// - new_root
 /// - make_root
+/// - reroot
 /// - parent_of
 /// - next_sib_of
 /// - prev_sib_of
 /// - first_child_of
 /// - last_child_of
+/// - sibling_index
+/// - nth_child
+/// - next_node
+/// - prev_node
+/// - range
+/// - node_at_path
+/// - path_of
 /// - move_to
-/// ### if impl Copy + Clone
+/// - group_children
+/// - promote
+/// - into_nested
+/// - replace_subtree
+/// - canonicalize_by
+/// - flatten
+/// - extract_subtrees
+/// - merge_by_key
+/// - merge_by_key_with
+/// - apply_delta
+/// ### if impl Clone
 /// - clone_to
-/// ### if impl IntoBytes
+/// - to_nested
+/// ### if impl Clone + PartialEq
+/// - delta_to
+/// ### if impl PartialEq
+/// - structural_eq
+/// - find_pattern
+/// ### if impl Hash
+/// - subtree_hash
+/// ### if impl IntoBytes (bytebuffer feature)
 /// - into_bytes
-/// ### if impl FromBytes
+/// - subtree_into_bytes
+/// - into_checked_bytes
+/// - into_offset_bytes
+/// - into_dedup_bytes
+/// - write_to
+/// - write_to_async (async feature)
+/// - into_bytes_compressed (compression feature)
+/// ### if impl FromBytes (bytebuffer feature)
 /// - from_bytes
 /// - from_io_bytes
-pub struct Tree<T> {
-    nodes: Vec<Node<T>>,
+/// - attach_from_bytes
+/// - attach_from_io_bytes
+/// - from_bytes_depth
+/// - from_bytes_limited
+/// - from_io_bytes_limited
+/// - from_checked_bytes
+/// - from_offset_bytes
+/// - read_at_path
+/// - from_dedup_bytes (if impl Clone)
+/// - from_dedup_io_bytes (if impl Clone)
+/// - from_checked_io_bytes
+/// - read_from
+/// - read_from_async (async feature)
+/// - from_bytes_compressed (compression feature)
+/// ### if impl Serialize / Deserialize (serde feature)
+/// - serialize
+/// - deserialize
+/// ### with the fs feature
+/// - from_dir
+/// ### with the petgraph feature
+/// - from_graph
+/// - to_graph (if impl Clone)
+/// ### with the testing feature
+/// - random
+/// ### with the json feature
+/// - to_json_string
+/// - from_json_str
+/// ### with the xml feature
+/// - to_xml
+/// - from_xml
+/// ### with the newick feature
+/// - to_newick
+/// - from_newick
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct Tree<T, #[cfg(feature = "allocator_api")] A: Allocator = Global> {
+    #[cfg(not(feature = "allocator_api"))]
+    nodes: Vec<Slot>,
+    #[cfg(feature = "allocator_api")]
+    nodes: Vec<Slot, A>,
+    //the data of every node packed together apart from the links, in no particular order.
+    #[cfg(not(feature = "allocator_api"))]
+    data: Vec<T>,
+    #[cfg(feature = "allocator_api")]
+    data: Vec<T, A>,
+    //the id of the node each piece of data belongs to.
+    #[cfg(not(feature = "allocator_api"))]
+    owners: Vec<Index>,
+    #[cfg(feature = "allocator_api")]
+    owners: Vec<Index, A>,
     free: Option<usize>,
     root: Option<usize>,
     len: usize,
@@ -118,6 +504,20 @@ impl<T> Tree<T> {
         Tree {
             free: None,
             nodes: Vec::new(),
+            data: Vec::new(),
+            owners: Vec::new(),
+            root: None,
+            len: 0,
+        }
+    }
+
+    /// Creates an empty tree with room for at least capacity nodes before it has to reallocate.
+    pub fn with_capacity(capacity: usize) -> Tree<T> {
+        Tree {
+            free: None,
+            nodes: Vec::with_capacity(capacity),
+            data: Vec::with_capacity(capacity),
+            owners: Vec::with_capacity(capacity),
             root: None,
             len: 0,
         }
@@ -128,33 +528,264 @@ impl<T> Tree<T> {
         Tree {
             free: None,
             nodes: vec![
-                Node::new(data),
+                Slot::Occupied(Node::new(), 0),
             ],
+            data: vec![data],
+            owners: vec![0],
             root: Some(0),
             len: 1,
         }
     }
 
+    //expands the seeds into children of the parent, keeping the seeds left to expand at each level on a stack so deep trees don't use up the call stack.
+    fn unfold_helper<S, F: FnMut(&S) -> (T, Vec<S>)>(&mut self, parent: usize, seeds: Vec<S>, f: &mut F) {
+        let mut levels = vec![(parent, seeds.into_iter())];
+
+        while let Some((parent, seeds)) = levels.last_mut() {
+            let parent = *parent;
+
+            match seeds.next() {
+                Some(seed) => {
+                    let (data, child_seeds) = f(&seed);
+                    let child = self.get_node(data);
+                    self.append_child(parent, child);
+                    levels.push((child, child_seeds.into_iter()));
+                }
+                None => { levels.pop(); }
+            }
+        }
+    }
+
+    /// Creates a tree by expanding the seed provided into the data of the root along with seeds for each of its children, and so on for every child.
+    pub fn unfold<S, F: FnMut(&S) -> (T, Vec<S>)>(seed: S, mut f: F) -> Tree<T> {
+        let (data, seeds) = f(&seed);
+        let mut tree = Tree::new_with_root(data);
+        tree.unfold_helper(0, seeds, &mut f);
+        tree
+    }
+
+    /// Creates a tree from a list of the data for each node along with the index of its parent in the list, the node without a parent becomes the root.
+    /// Each node gets its index in the list as its id and children keep the order they have in the list.
+    /// Fails if there isn't exactly one root, if a parent index is outside of the list, or if the parents form a cycle.
+    pub fn from_parent_list<I: IntoIterator<Item = (Option<usize>, T)>>(items: I) -> Result<Tree<T>, TreeErr> {
+        let mut parents = Vec::new();
+        let mut data = Vec::new();
+
+        for (parent, item) in items {
+            parents.push(parent);
+            data.push(item);
+        }
+
+        let nodes: Vec<Slot> = (0..data.len()).map(|id| Slot::Occupied(Node::new(), narrow(id))).collect();
+
+        let mut children = vec![Vec::new(); nodes.len()];
+        let mut root = None;
+
+        for (id, &parent) in parents.iter().enumerate() {
+            match parent {
+                Some(parent_id) if parent_id >= nodes.len() => return Err(InvalidId),
+                Some(parent_id) => children[parent_id].push(id),
+                None if root.is_some() => return Err(MultipleRoots),
+                None => root = Some(id),
+            }
+        }
+
+        let mut tree = Tree {
+            len: nodes.len(),
+            owners: (0..nodes.len()).map(narrow).collect(),
+            nodes,
+            data,
+            free: None,
+            root: None,
+        };
+
+        if tree.nodes.is_empty() { return Ok(tree) }
+        let root = root.ok_or(NoRoot)?;
+
+        //every node has to be reachable from the root, otherwise some of the nodes are in a cycle.
+        let mut reached = 0;
+        let mut stack = vec![root];
+
+        while let Some(id) = stack.pop() {
+            reached += 1;
+            for &child in &children[id] {
+                tree.append_child(id, child);
+                stack.push(child);
+            }
+        }
+
+        if reached != tree.nodes.len() { return Err(Cycle) }
+        tree.root = Some(root);
+
+        Ok(tree)
+    }
+
+    /// Creates a tree from the data of each node and a list of parent and child index pairs, each node gets its index in the list of nodes as its id.
+    /// Children keep the order of their edges. Fails with InvalidId if an edge points outside of the nodes, MultipleParents if a node is the child
+    /// of more than one edge, MultipleRoots or NoRoot if there isn't exactly one node without a parent, and Cycle if the edges loop.
+    pub fn from_edges<N: IntoIterator<Item = T>, E: IntoIterator<Item = (usize, usize)>>(nodes: N, edges: E) -> Result<Tree<T>, TreeErr> {
+        let nodes: Vec<T> = nodes.into_iter().collect();
+        let mut parents = vec![None; nodes.len()];
+        let mut children = vec![Vec::new(); nodes.len()];
+
+        for (parent, child) in edges {
+            if parent >= nodes.len() || child >= nodes.len() { return Err(InvalidId) }
+            if parents[child].replace(parent).is_some() { return Err(MultipleParents) }
+            children[parent].push(child);
+        }
+
+        let mut tree = Tree::from_parent_list(parents.into_iter().zip(nodes))?;
+
+        for (parent, children) in children.iter().enumerate() {
+            if children.len() > 1 {
+                tree.relink_children(parent, children);
+            }
+        }
+
+        Ok(tree)
+    }
+
+    /// Creates a tree from nested set rows of the left number, right number and data of each node, where the numbers of each node are between the
+    /// numbers of its parent. Children are ordered by their left numbers.
+    /// Fails if the numbers of two nodes overlap without one being inside the other, or if more than one node isn't inside any other.
+    pub fn from_nested_set<I: IntoIterator<Item = (usize, usize, T)>>(items: I) -> Result<Tree<T>, TreeErr> {
+        let mut items: Vec<_> = items.into_iter().collect();
+        items.sort_by_key(|item| item.0);
+
+        let mut tree = Tree::new();
+        let mut open: Vec<(usize, usize)> = Vec::new();
+        let mut last_left = None;
+
+        for (left, right, data) in items {
+            if left >= right || last_left == Some(left) { return Err(InvalidNesting) }
+            last_left = Some(left);
+
+            while open.last().is_some_and(|&(_, open_right)| open_right < left) {
+                open.pop();
+            }
+
+            let id = match open.last() {
+                Some(&(parent, parent_right)) => {
+                    if right > parent_right { return Err(InvalidNesting) }
+
+                    let id = tree.get_node(data);
+                    tree.append_child(parent, id);
+                    id
+                }
+                None if tree.root.is_none() => tree.new_root(data),
+                None => return Err(MultipleRoots),
+            };
+
+            open.push((id, right));
+        }
+
+        Ok(tree)
+    }
+
+    /// Creates a tree with the root provided from a list of paths split by the separator, like a list of file paths.
+    /// Each part of a path becomes a node made by make_node, and paths that start with the same parts share the nodes for those parts. Empty parts are skipped.
+    pub fn from_paths<I, S, F>(root: T, paths: I, separator: &str, mut make_node: F) -> Tree<T>
+    where I: IntoIterator<Item = S>, S: AsRef<str>, F: FnMut(&str) -> T
+    {
+        let mut tree = Tree::new_with_root(root);
+        let mut made: HashMap<(usize, String), usize> = HashMap::new();
+
+        for path in paths {
+            let mut parent = 0;
+
+            for part in path.as_ref().split(separator).filter(|part| !part.is_empty()) {
+                parent = match made.entry((parent, part.to_string())) {
+                    Entry::Occupied(entry) => *entry.get(),
+                    Entry::Vacant(entry) => {
+                        let id = tree.get_node(make_node(part));
+                        tree.append_child(parent, id);
+                        *entry.insert(id)
+                    }
+                };
+            }
+        }
+
+        tree
+    }
+
+    /// Creates a tree from text with one node on each line, made by parse_line from the line without its indentation.
+    /// Each line is a child of the closest line above it that is indented less, blank lines are skipped.
+    /// Fails with MultipleRoots if more than one line has the least indentation.
+    pub fn from_indented<F: FnMut(&str) -> T>(text: &str, mut parse_line: F) -> Result<Tree<T>, TreeErr> {
+        let mut tree = Tree::new();
+        let mut open: Vec<(usize, usize)> = Vec::new();
+
+        for line in text.lines() {
+            let content = line.trim_start();
+            if content.trim_end().is_empty() { continue }
+
+            let indent = line.len() - content.len();
+
+            while open.last().is_some_and(|&(open_indent, _)| open_indent >= indent) {
+                open.pop();
+            }
+
+            let data = parse_line(content.trim_end());
+
+            let id = match open.last() {
+                Some(&(_, parent)) => {
+                    let id = tree.get_node(data);
+                    tree.append_child(parent, id);
+                    id
+                }
+                None if tree.root.is_none() => tree.new_root(data),
+                None => return Err(MultipleRoots),
+            };
+
+            open.push((indent, id));
+        }
+
+        Ok(tree)
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<T, A: Allocator + Clone> Tree<T, A> {
+    /// Creates an empty tree that keeps its nodes in the given allocator.
+    pub fn new_in(alloc: A) -> Tree<T, A> {
+        Tree::with_capacity_in(0, alloc)
+    }
+
+    /// Creates an empty tree in the given allocator with room for at least capacity nodes before it has to reallocate.
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Tree<T, A> {
+        Tree {
+            free: None,
+            nodes: Vec::with_capacity_in(capacity, alloc.clone()),
+            data: Vec::with_capacity_in(capacity, alloc.clone()),
+            owners: Vec::with_capacity_in(capacity, alloc),
+            root: None,
+            len: 0,
+        }
+    }
+
+    /// Returns the allocator the tree keeps its nodes in.
+    pub fn allocator(&self) -> &A {
+        self.nodes.allocator()
+    }
+}
+
+impl_in_any_allocator! {
     fn valid_node(&self, id: usize) -> Result<(), TreeErr> {
         if id >= self.nodes.len() { return Err(InvalidId) }
-        if self.nodes[id].data.is_none() { return Err(InvalidId) }
+        if self.data_of(id).is_none() { return Err(InvalidId) }
         Ok(())
     }
 
     fn valid_sib(&self, id: usize) -> Result<(), TreeErr> {
         if id >= self.nodes.len() { return Err(InvalidId) }
-        if self.nodes[id].data.is_none() { return Err(InvalidId) }
+        if self.data_of(id).is_none() { return Err(InvalidId) }
         if id == self.root.unwrap() { return Err(CantBeRoot) }
         Ok(())
     }
 
     fn push_free(&mut self, id: usize) {
-        self.nodes[id].parent      = None;
-        self.nodes[id].prev_sib    = None;
-        self.nodes[id].next_sib    = self.free;
-        self.nodes[id].first_child = None;
-        self.nodes[id].last_child  = None;
-        self.nodes[id].data        = None;
+        self.take_data(id);
+        self.nodes[id] = Slot::Free(Link::new(self.free));
 
         self.free = Some(id);
         self.len -= 1;
@@ -162,8 +793,10 @@ impl<T> Tree<T> {
 
     fn pop_free(&mut self) -> Option<usize> {
         if let Some(id) = self.free {
-            self.free = self.nodes[id].next_sib;
-            self.nodes[id].next_sib = None;
+            self.free = match self.nodes[id] {
+                Slot::Free(next) => next.get(),
+                _ => unreachable!("the free list only links free slots"),
+            };
 
             return Some(id);
         }
@@ -174,82 +807,151 @@ impl<T> Tree<T> {
     fn get_node(&mut self, data: T) -> usize {
         self.len += 1;
         
-        if let Some(id) = self.pop_free() {
-            self.nodes[id].data = Some(data);
+        let id = match self.pop_free() {
+            Some(id) => id,
+            None => {
+                self.nodes.push(Slot::Free(Link::NONE));
+                self.nodes.len() - 1
+            }
+        };
+
+        self.put_data(id, data);
+        id
+    }
+
+    //gives the free slot of the given id a new node holding the data.
+    fn put_data(&mut self, id: usize, data: T) {
+        self.nodes[id] = Slot::Occupied(Node::new(), narrow(self.data.len()));
+        self.data.push(data);
+        self.owners.push(narrow(id));
+    }
 
-            return id;
+    fn data_of(&self, id: usize) -> Option<&T> {
+        match self.nodes.get(id)? {
+            Slot::Occupied(_, index) => Some(&self.data[widen(*index)]),
+            _ => None,
         }
-        else {
-            self.nodes.push(Node::new(data));
+    }
+
+    fn data_of_mut(&mut self, id: usize) -> Option<&mut T> {
+        match self.nodes.get(id)? {
+            Slot::Occupied(_, index) => Some(&mut self.data[widen(*index)]),
+            _ => None,
+        }
+    }
 
-            return self.nodes.len() - 1;
+    //takes the data out of the node, leaving it linked in as it was. The last data is moved into the gap.
+    fn take_data(&mut self, id: usize) -> Option<T> {
+        let (node, index) = match self.nodes[id] {
+            Slot::Occupied(node, index) => (node, widen(index)),
+            _ => return None,
+        };
+        self.nodes[id] = Slot::Hidden(node);
+
+        let data = self.data.swap_remove(index);
+        self.owners.swap_remove(index);
+        if let Some(&moved) = self.owners.get(index) {
+            if let Slot::Occupied(_, moved_index) = &mut self.nodes[widen(moved)] {
+                *moved_index = narrow(index);
+            }
         }
+
+        Some(data)
     }
 
     fn append_child(&mut self, parent_id: usize, new_id: usize) {
+        self.link_last_child(parent_id, new_id);
+        #[cfg(feature = "subtree_sizes")]
+        self.grow_ancestors(new_id);
+        self.fix_depths(new_id);
+    }
+
+    //links the node in as the last child of the parent, leaving the sizes and depths for the caller to fix.
+    fn link_last_child(&mut self, parent_id: usize, new_id: usize) {
         //previous sibling of new set to the parents last child.
         self.nodes[new_id].prev_sib = self.nodes[parent_id].last_child;
-        if let Some(prev) = self.nodes[new_id].prev_sib {
-            self.nodes[prev].next_sib = Some(new_id);
+        if let Some(prev) = self.nodes[new_id].prev_sib.get() {
+            self.nodes[prev].next_sib = Link::some(new_id);
         }
 
-        self.nodes[new_id].parent = Some(parent_id);
+        self.nodes[new_id].parent = Link::some(parent_id);
 
         //last child of parent updated to be the new node.
-        self.nodes[parent_id].last_child = Some(new_id);
+        self.nodes[parent_id].last_child = Link::some(new_id);
 
         //if the parent didn't have any children new is set also set to the first child.
-        if self.nodes[parent_id].first_child.is_none() {
-            self.nodes[parent_id].first_child = Some(new_id);
+        if self.nodes[parent_id].first_child.get().is_none() {
+            self.nodes[parent_id].first_child = Link::some(new_id);
         }
+
+        self.nodes[parent_id].child_count += 1;
     }
 
     fn prepend_child(&mut self, parent_id: usize, new_id: usize){
         //next sibling of new set to the parents first child.
         self.nodes[new_id].next_sib = self.nodes[parent_id].first_child;
-        if let Some(next) = self.nodes[new_id].next_sib {
-            self.nodes[next].prev_sib = Some(new_id);
+        if let Some(next) = self.nodes[new_id].next_sib.get() {
+            self.nodes[next].prev_sib = Link::some(new_id);
         }
 
-        self.nodes[new_id].parent = Some(parent_id);
+        self.nodes[new_id].parent = Link::some(parent_id);
 
         //first child of parent updated to be the new node.
-        self.nodes[parent_id].first_child = Some(new_id);
+        self.nodes[parent_id].first_child = Link::some(new_id);
 
         //if the parent didn't have any children new is set also set to the last child.
-        if self.nodes[parent_id].last_child.is_none() {
-            self.nodes[parent_id].last_child = Some(new_id);
+        if self.nodes[parent_id].last_child.get().is_none() {
+            self.nodes[parent_id].last_child = Link::some(new_id);
         }
+
+        self.nodes[parent_id].child_count += 1;
+        #[cfg(feature = "subtree_sizes")]
+        self.grow_ancestors(new_id);
+        self.fix_depths(new_id);
     }
 
     fn add_sibling_before(&mut self, sibling_id: usize, new_id: usize) {
-        self.nodes[new_id].next_sib = Some(sibling_id);
+        self.nodes[new_id].next_sib = Link::some(sibling_id);
         self.nodes[new_id].prev_sib = self.nodes[sibling_id].prev_sib;
         self.nodes[new_id].parent = self.nodes[sibling_id].parent;
 
-        self.nodes[sibling_id].prev_sib = Some(new_id);
+        self.nodes[sibling_id].prev_sib = Link::some(new_id);
         
-        if let Some(prev_sib_id) = self.nodes[new_id].prev_sib {
-            self.nodes[prev_sib_id].next_sib = Some(new_id);
+        if let Some(prev_sib_id) = self.nodes[new_id].prev_sib.get() {
+            self.nodes[prev_sib_id].next_sib = Link::some(new_id);
         }
-        else if let Some(parent_id) = self.nodes[new_id].parent {
-            self.nodes[parent_id].first_child = Some(new_id);
+        else if let Some(parent_id) = self.nodes[new_id].parent.get() {
+            self.nodes[parent_id].first_child = Link::some(new_id);
         }
+
+        if let Some(parent_id) = self.nodes[new_id].parent.get() {
+            self.nodes[parent_id].child_count += 1;
+        }
+        #[cfg(feature = "subtree_sizes")]
+        self.grow_ancestors(new_id);
+        self.fix_depths(new_id);
     }
 
     fn add_sibling_after(&mut self, sibling_id: usize, new_id: usize) {
-        self.nodes[new_id].prev_sib = Some(sibling_id);
+        self.nodes[new_id].prev_sib = Link::some(sibling_id);
         self.nodes[new_id].next_sib = self.nodes[sibling_id].next_sib;
         self.nodes[new_id].parent = self.nodes[sibling_id].parent;
 
-        self.nodes[sibling_id].next_sib = Some(new_id);
+        self.nodes[sibling_id].next_sib = Link::some(new_id);
 
-        if let Some(next_sib_id) = self.nodes[new_id].next_sib {
-            self.nodes[next_sib_id].prev_sib = Some(new_id);
+        if let Some(next_sib_id) = self.nodes[new_id].next_sib.get() {
+            self.nodes[next_sib_id].prev_sib = Link::some(new_id);
         }
-        else if let Some(parent_id) = self.nodes[new_id].parent {
-            self.nodes[parent_id].last_child = Some(new_id);
+        else if let Some(parent_id) = self.nodes[new_id].parent.get() {
+            self.nodes[parent_id].last_child = Link::some(new_id);
+        }
+
+        if let Some(parent_id) = self.nodes[new_id].parent.get() {
+            self.nodes[parent_id].child_count += 1;
         }
+        #[cfg(feature = "subtree_sizes")]
+        self.grow_ancestors(new_id);
+        self.fix_depths(new_id);
     }
 
     fn attach(&mut self, attaching: usize, in_position: Position, node: usize){
@@ -261,23 +963,75 @@ impl<T> Tree<T> {
         }
     }
 
+    /// Adds the size of the given node to the sizes of all of its ancestors.
+    #[cfg(feature = "subtree_sizes")]
+    fn grow_ancestors(&mut self, id: usize) {
+        let size = self.nodes[id].size;
+        let mut parent = self.nodes[id].parent.get();
+
+        while let Some(parent_id) = parent {
+            self.nodes[parent_id].size += size;
+            parent = self.nodes[parent_id].parent.get();
+        }
+    }
+
+    /// Sets the depth of the given node from its parent, moving the depths of its descendants along with it.
+    fn fix_depths(&mut self, id: usize) {
+        let depth = match self.nodes[id].parent.get() {
+            Some(parent) => self.nodes[parent].depth + 1,
+            None => 0,
+        };
+        let old = self.nodes[id].depth;
+        if depth == old { return }
+
+        let mut node = Some(id);
+        while let Some(node_id) = node {
+            self.nodes[node_id].depth = self.nodes[node_id].depth + depth - old;
+            node = self.next_in_sub_tree(node_id, id);
+        }
+    }
+
+    /// Removes the size of the given node from the sizes of all of its ancestors.
+    #[cfg(feature = "subtree_sizes")]
+    fn shrink_ancestors(&mut self, id: usize) {
+        let size = self.nodes[id].size;
+        let mut parent = self.nodes[id].parent.get();
+
+        while let Some(parent_id) = parent {
+            self.nodes[parent_id].size -= size;
+            parent = self.nodes[parent_id].parent.get();
+        }
+    }
+
     fn decouple(&mut self, id: usize){
-        if let Some(prev) = self.nodes[id].prev_sib {
+        #[cfg(feature = "subtree_sizes")]
+        self.shrink_ancestors(id);
+        self.unlink(id);
+    }
+
+    //takes the node out from between its parent and siblings, leaving the sizes of its ancestors for the caller to fix.
+    fn unlink(&mut self, id: usize){
+        if let Some(prev) = self.nodes[id].prev_sib.get() {
             self.nodes[prev].next_sib = self.nodes[id].next_sib;
         }
-        else if let Some(parent) = self.nodes[id].parent {
+        else if let Some(parent) = self.nodes[id].parent.get() {
             self.nodes[parent].first_child = self.nodes[id].next_sib;
         }
 
-        if let Some(next) = self.nodes[id].next_sib {
+        if let Some(next) = self.nodes[id].next_sib.get() {
             self.nodes[next].prev_sib = self.nodes[id].prev_sib;
         }
-        else if let Some(parent) = self.nodes[id].parent {
+        else if let Some(parent) = self.nodes[id].parent.get() {
             self.nodes[parent].last_child = self.nodes[id].prev_sib;
         }
 
-        self.nodes[id].prev_sib = None;
-        self.nodes[id].next_sib = None;
+        if let Some(parent) = self.nodes[id].parent.get() {
+            self.nodes[parent].child_count -= 1;
+        }
+
+        self.nodes[id].parent   = Link::NONE;
+        self.nodes[id].prev_sib = Link::NONE;
+        self.nodes[id].next_sib = Link::NONE;
     }
 
     /// Returns the number of nodes currently in the tree.
@@ -285,145 +1039,772 @@ impl<T> Tree<T> {
         self.len
     }
 
-    fn descendants_of_helper(&self, id: usize, ids: &mut Vec<usize>){
-        let mut child = self.nodes[id].first_child;
+    /// Returns the number of nodes the tree can hold before it has to reallocate.
+    pub fn capacity(&self) -> usize {
+        self.nodes.capacity().min(self.data.capacity()).min(self.owners.capacity())
+    }
 
-        while let Some(child_id) = child {
-            ids.push(child_id);
-            self.descendants_of_helper(child_id, ids);
-            child = self.nodes[child_id].next_sib;
-        }
+    /// Returns the number of slots left free by removed nodes, which are used again by new nodes before the storage grows.
+    pub fn free_count(&self) -> usize {
+        self.nodes.len() - self.len
     }
 
-    /// Returns a list of all of the descendants of the provided id.
-    pub fn descendants_of(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
-        self.valid_node(id)?;
+    /// Returns roughly how many bytes the tree is using, counting the room it has reserved but not memory owned by the data itself.
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.nodes.capacity() * std::mem::size_of::<Slot>()
+            + self.data.capacity() * std::mem::size_of::<T>()
+            + self.owners.capacity() * std::mem::size_of::<Index>()
+    }
 
-        let mut ids = Vec::with_capacity(self.len());
-        self.descendants_of_helper(id, &mut ids);
+    /// Makes room for at least additional more nodes than are currently in the tree, free slots left by removed nodes count towards the room.
+    pub fn reserve(&mut self, additional: usize) {
+        let free_slots = self.nodes.len() - self.data.len();
+        self.nodes.reserve(additional.saturating_sub(free_slots));
+        self.data.reserve(additional);
+        self.owners.reserve(additional);
+    }
 
-        Ok(ids)
+    /// The same as reserve, but returns an error instead of panicking if the memory can't be allocated.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), std::collections::TryReserveError> {
+        let free_slots = self.nodes.len() - self.data.len();
+        self.nodes.try_reserve(additional.saturating_sub(free_slots))?;
+        self.data.try_reserve(additional)?;
+        self.owners.try_reserve(additional)
     }
 
-    /// Returns a list starting with the id provided followed by all of its descendants.
-    pub fn sub_tree(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
-        self.valid_node(id)?;
+    /// Gives back as much unused memory as possible. Free slots left by removed nodes keep their place so the ids of the other nodes don't change.
+    pub fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        self.data.shrink_to_fit();
+        self.owners.shrink_to_fit();
+    }
 
-        let mut ids = Vec::with_capacity(self.len());
-        ids.push(id);
-        self.descendants_of_helper(id, &mut ids);
+    /// Moves the nodes down into the free slots left by removed nodes so the storage can shrink, keeping the nodes in the same order by id.
+    /// Returns a table of the new id of each old id, None for old ids that weren't nodes.
+    pub fn compact(&mut self) -> Vec<Option<usize>> {
+        let order: Vec<usize> = (0..self.nodes.len()).filter(|&id| self.valid_node(id).is_ok()).collect();
+        let remap = self.relabel(&order);
+        self.shrink_to_fit();
+        remap
+    }
 
-        Ok(ids)
+    /// The same as compact, but the nodes are laid out in the same order as sub_tree from the root, so crawling down the tree walks through memory in order.
+    /// Returns a table of the new id of each old id, None for old ids that weren't nodes.
+    pub fn reorder_dfs(&mut self) -> Vec<Option<usize>> {
+        let order = match self.root {
+            Some(root) => self.sub_tree(root).unwrap(),
+            None => Vec::new(),
+        };
+        let remap = self.relabel(&order);
+        self.shrink_to_fit();
+        remap
     }
 
-    fn sub_tree_info_helper(&self, id: usize, ids: &mut Vec<NodeInfo>, cur_depth: usize){
-        let index = ids.len();
-        ids.push(NodeInfo{
-            id: id,
-            child_count: 0,
-            depth: cur_depth,
-        });
+    /// Returns whether the given id is currently a node in the tree.
+    pub fn contains(&self, id: usize) -> bool {
+        self.valid_node(id).is_ok()
+    }
 
-        let mut child = self.nodes[id].first_child;
+    /// Returns the number of nodes in the sub tree of the given id, including itself.
+    /// Is O(1) with the subtree_sizes feature, otherwise the sub tree is walked.
+    pub fn subtree_size(&self, id: usize) -> Result<usize, TreeErr> {
+        self.valid_node(id)?;
 
-        while let Some(child_id) = child {
-            ids[index].child_count += 1;
-            self.sub_tree_info_helper(child_id, ids, cur_depth + 1);
-            child = self.nodes[child_id].next_sib;
-        }
+        Ok(self.size_of(id))
     }
 
-    /// Returns a list starting with the id provided and the number of children it has followed by the same for all of its descendants.
-    pub fn sub_tree_info(&self, id: usize) -> Result<Vec<NodeInfo>, TreeErr> {
-        self.valid_node(id)?;
+    #[cfg(feature = "subtree_sizes")]
+    fn size_of(&self, id: usize) -> usize {
+        widen(self.nodes[id].size)
+    }
 
-        let mut ids = Vec::new();
-        self.sub_tree_info_helper(id, &mut ids, 0);
+    #[cfg(not(feature = "subtree_sizes"))]
+    fn size_of(&self, id: usize) -> usize {
+        let mut size = 0;
+        let mut node = Some(id);
 
-        Ok(ids)
+        while let Some(node_id) = node {
+            size += 1;
+            node = self.next_in_sub_tree(node_id, id);
+        }
+
+        size
     }
 
-    fn sub_tree_depth_helper(&self, id: usize, ids: &mut Vec<usize>, depth: usize){
-        let mut child = self.nodes[id].first_child;
+    /// Returns the position of the given node in the whole tree, in the same order as sub_tree from the root.
+    /// Only the earlier siblings of the node and of each of its ancestors are looked at.
+    #[cfg(feature = "subtree_sizes")]
+    pub fn rank_of(&self, id: usize) -> Result<usize, TreeErr> {
+        self.valid_node(id)?;
 
-        while let Some(child_id) = child {
-            ids.push(child_id);
+        let mut rank = 0;
+        let mut node = id;
 
-            if depth > 0 {
-                self.sub_tree_depth_helper(child_id, ids, depth - 1);
+        loop {
+            let mut prev = self.nodes[node].prev_sib.get();
+            while let Some(prev_id) = prev {
+                rank += self.size_of(prev_id);
+                prev = self.nodes[prev_id].prev_sib.get();
             }
 
-            child = self.nodes[child_id].next_sib;
+            match self.nodes[node].parent.get() {
+                Some(parent) => {
+                    rank += 1;
+                    node = parent;
+                }
+                None => return Ok(rank),
+            }
         }
     }
 
-    /// Returns a list starting with the id provided followed by all of its descendants up to the given depth.
-    pub fn sub_tree_depth(&self, id: usize, depth: usize) -> Result<Vec<usize>, TreeErr> {
-        self.valid_node(id)?;
+    /// Returns the id of the node at the given position in the whole tree, in the same order as sub_tree from the root.
+    /// Whole sub trees that come before the position are skipped over by their size. Fails with OutOfRange if the position isn't in the tree.
+    #[cfg(feature = "subtree_sizes")]
+    pub fn node_at_rank(&self, rank: usize) -> Result<usize, TreeErr> {
+        if rank >= self.len { return Err(OutOfRange) }
 
-        let mut ids = Vec::with_capacity(self.len());
-        ids.push(id);
+        let mut node = self.root.ok_or(OutOfRange)?;
+        let mut left = rank;
+
+        while left > 0 {
+            left -= 1;
+            let mut child = self.nodes[node].first_child.get().unwrap();
+
+            while left >= self.size_of(child) {
+                left -= self.size_of(child);
+                child = self.nodes[child].next_sib.get().unwrap();
+            }
 
-        if depth > 0 {
-            self.sub_tree_depth_helper(id, &mut ids, depth - 1);
+            node = child;
         }
-        
-        Ok(ids)
-    }
 
-    fn sub_tree_depth_info_helper(&self, id: usize, ids: &mut Vec<NodeInfo>, cur_depth: usize, target: usize){
-        let index = ids.len();
-        ids.push(NodeInfo{
-            id: id,
-            child_count: 0,
-            depth: cur_depth,
-        });
+        Ok(node)
+    }
 
-        let mut child = self.nodes[id].first_child;
+    /// Returns a summary of the shape of the tree.
+    pub fn stats(&self) -> TreeStats {
+        let mut stats = TreeStats {
+            node_count: self.len,
+            height: 0,
+            leaf_count: 0,
+            max_children: 0,
+            level_counts: Vec::new(),
+        };
 
-        while let Some(child_id) = child {
-            ids[index].child_count += 1;
+        if let Some(root) = self.root {
+            for node in self.sub_tree_info(root).unwrap() {
+                if node.child_count == 0 { stats.leaf_count += 1 }
+                stats.max_children = stats.max_children.max(node.child_count);
 
-            if cur_depth < target {
-                self.sub_tree_depth_info_helper(child_id, ids, cur_depth + 1, target);
+                if node.depth == stats.level_counts.len() {
+                    stats.level_counts.push(0);
+                }
+                stats.level_counts[node.depth] += 1;
             }
 
-            child = self.nodes[child_id].next_sib;
+            stats.height = stats.level_counts.len() - 1;
         }
+
+        stats
     }
 
-    /// Returns a list starting with the id provided and the number of children it has followed by the same for all of its descendants up to the given depth.
-    pub fn sub_tree_depth_info(&self, id: usize, depth: usize) -> Result<Vec<NodeInfo>, TreeErr> {
-        self.valid_node(id)?;
+    //adds the descendants of the id to the list in the same order as sub_tree, going at most max_depth levels below it.
+    fn descendants_of_helper(&self, id: usize, ids: &mut Vec<usize>, max_depth: usize){
+        let mut depth = 0;
+        let mut node = self.next_in_sub_tree_to_depth(id, id, &mut depth, max_depth);
 
-        let mut ids = Vec::new();
+        while let Some(node_id) = node {
+            ids.push(node_id);
+            node = self.next_in_sub_tree_to_depth(node_id, id, &mut depth, max_depth);
+        }
+    }
+
+    /// Returns a list of all of the descendants of the provided id.
+    pub fn descendants_of(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
+        self.valid_node(id)?;
 
-        self.sub_tree_depth_info_helper(id, &mut ids, 0, depth);
+        let mut ids = Vec::with_capacity(self.len());
+        self.descendants_of_helper(id, &mut ids, usize::MAX);
 
         Ok(ids)
     }
 
-    /// Returns a list of all of the child ids of the given node.
-    pub fn children_of(&self, id: usize) -> Result<Vec<usize>, TreeErr>{
+    /// Returns a list starting with the id provided followed by all of its descendants.
+    pub fn sub_tree(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
         self.valid_node(id)?;
 
-        let mut children = Vec::new();
-        let mut child = self.first_child_of(id).unwrap();
-
-        while let Some(child_id) = child{
-            children.push(child_id);
-            child = self.next_sib_of(child_id).unwrap();
-        }
+        let mut ids = Vec::with_capacity(self.len());
+        ids.push(id);
+        self.descendants_of_helper(id, &mut ids, usize::MAX);
 
-        Ok(children)
+        Ok(ids)
     }
 
-    /// Creates a new node containing the data provide and attaches it to the node provide in the position of in_position.
-    pub fn new_node(&mut self, data: T, in_position: Position, node: usize) -> Result<usize, TreeErr> {
+    //lists the id and every descendant up to max_depth levels below it with their child counts, which count all of the children even past max_depth.
+    fn sub_tree_info_helper(&self, id: usize, max_depth: usize) -> Vec<NodeInfo> {
+        let mut ids = Vec::new();
+        let mut depth = 0;
+        let mut node = Some(id);
+
+        while let Some(node_id) = node {
+            ids.push(NodeInfo{
+                id: node_id,
+                child_count: self.count_children(node_id),
+                depth,
+            });
+
+            node = self.next_in_sub_tree_to_depth(node_id, id, &mut depth, max_depth);
+        }
+
+        ids
+    }
+
+    /// Returns a list starting with the id provided and the number of children it has followed by the same for all of its descendants.
+    pub fn sub_tree_info(&self, id: usize) -> Result<Vec<NodeInfo>, TreeErr> {
+        self.valid_node(id)?;
+
+        Ok(self.sub_tree_info_helper(id, usize::MAX))
+    }
+
+    /// Returns a list starting with the id provided followed by all of its descendants up to the given depth.
+    pub fn sub_tree_depth(&self, id: usize, depth: usize) -> Result<Vec<usize>, TreeErr> {
+        self.valid_node(id)?;
+
+        let mut ids = Vec::with_capacity(self.len());
+        ids.push(id);
+        self.descendants_of_helper(id, &mut ids, depth);
+        
+        Ok(ids)
+    }
+
+    /// Returns a list starting with the id provided and the number of children it has followed by the same for all of its descendants up to the given depth.
+    pub fn sub_tree_depth_info(&self, id: usize, depth: usize) -> Result<Vec<NodeInfo>, TreeErr> {
+        self.valid_node(id)?;
+
+        Ok(self.sub_tree_info_helper(id, depth))
+    }
+
+    /// Returns a list of all of the child ids of the given node.
+    pub fn children_of(&self, id: usize) -> Result<Vec<usize>, TreeErr>{
+        self.valid_node(id)?;
+
+        let mut children = Vec::new();
+        let mut child = self.first_child_of(id).unwrap();
+
+        while let Some(child_id) = child{
+            children.push(child_id);
+            child = self.next_sib_of(child_id).unwrap();
+        }
+
+        Ok(children)
+    }
+
+    /// Returns the number of levels below the given node, following the longest path down to a leaf. A node without children has a height of zero.
+    pub fn height_of(&self, id: usize) -> Result<usize, TreeErr> {
+        Ok(self.sub_tree_info(id)?.iter().map(|node| node.depth).max().unwrap())
+    }
+
+    /// Returns the number of ancestors the given node has, the root has a depth of zero.
+    pub fn depth_of(&self, id: usize) -> Result<usize, TreeErr> {
+        self.valid_node(id)?;
+
+        Ok(widen(self.nodes[id].depth))
+    }
+
+    /// Returns whether the first node is an ancestor of the second node, following the parents of the second node.
+    pub fn is_ancestor_of(&self, ancestor: usize, id: usize) -> Result<bool, TreeErr> {
+        self.valid_node(ancestor)?;
+        self.valid_node(id)?;
+
+        let mut parent = self.nodes[id].parent.get();
+
+        while let Some(parent_id) = parent {
+            if parent_id == ancestor { return Ok(true) }
+            parent = self.nodes[parent_id].parent.get();
+        }
+
+        Ok(false)
+    }
+
+    /// Returns whether the first node is a descendant of the second node.
+    pub fn is_descendant_of(&self, id: usize, ancestor: usize) -> Result<bool, TreeErr> {
+        self.is_ancestor_of(ancestor, id)
+    }
+
+    /// Returns the number of children the given node has, without going through them.
+    pub fn child_count(&self, id: usize) -> Result<usize, TreeErr> {
+        self.valid_node(id)?;
+
+        Ok(self.count_children(id))
+    }
+
+    /// Returns whether the given node has no children.
+    pub fn is_leaf(&self, id: usize) -> Result<bool, TreeErr> {
+        self.valid_node(id)?;
+
+        Ok(self.nodes[id].first_child.get().is_none())
+    }
+
+    /// Returns whether the given node is the root of the tree.
+    pub fn is_root(&self, id: usize) -> Result<bool, TreeErr> {
+        self.valid_node(id)?;
+
+        Ok(self.root == Some(id))
+    }
+
+    /// Returns a list starting with the id provided followed by each of its ancestors up to and including the root.
+    pub fn path_to_root(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
+        self.valid_node(id)?;
+
+        let mut path = vec![id];
+        let mut parent = self.nodes[id].parent.get();
+
+        while let Some(parent_id) = parent {
+            path.push(parent_id);
+            parent = self.nodes[parent_id].parent.get();
+        }
+
+        Ok(path)
+    }
+
+    /// Returns a list of the nodes going from the first node up to the closest ancestor the two nodes share and back down to the second node.
+    pub fn path_between(&self, from: usize, to: usize) -> Result<Vec<usize>, TreeErr> {
+        let mut up   = self.path_to_root(from)?;
+        let mut down = self.path_to_root(to)?;
+
+        //remove the shared ancestors, remembering the closest one.
+        let mut shared = None;
+        while !up.is_empty() && up.last() == down.last() {
+            shared = up.pop();
+            down.pop();
+        }
+
+        up.push(shared.unwrap());
+        up.extend(down.into_iter().rev());
+
+        Ok(up)
+    }
+
+    /// Returns the number of steps between the two nodes along path_between.
+    pub fn distance(&self, from: usize, to: usize) -> Result<usize, TreeErr> {
+        Ok(self.path_between(from, to)?.len() - 1)
+    }
+
+    /// Returns the node after the given one in the sub tree of top, going through the nodes in the same order as sub_tree.
+    fn next_in_sub_tree(&self, id: usize, top: usize) -> Option<usize> {
+        if let Some(child) = self.nodes[id].first_child.get() {
+            return Some(child);
+        }
+
+        let mut node = id;
+
+        while node != top {
+            if let Some(next) = self.nodes[node].next_sib.get() {
+                return Some(next);
+            }
+
+            node = self.nodes[node].parent.get().unwrap();
+        }
+
+        None
+    }
+
+    /// Steps through the sub tree of top in the same order as sub_tree, calling visit when entering each node before its children and again when
+    /// leaving it after them. Follows the links instead of recursing, so deep trees can't overflow the stack.
+    fn walk_sub_tree<F: FnMut(usize, Visit)>(&self, top: usize, mut visit: F) {
+        let mut node = top;
+
+        loop {
+            visit(node, Visit::Enter);
+
+            if let Some(child) = self.nodes[node].first_child.get() {
+                node = child;
+                continue;
+            }
+
+            //leaves the node and each ancestor it is the last descendant of, until one has a next sibling.
+            loop {
+                visit(node, Visit::Leave);
+                if node == top { return }
+
+                match self.nodes[node].next_sib.get() {
+                    Some(next) => {
+                        node = next;
+                        break;
+                    }
+                    None => node = self.nodes[node].parent.get().unwrap(),
+                }
+            }
+        }
+    }
+
+    /// Steps through the sub tree of top like next_in_sub_tree, keeping track of the depth below top and not going past max_depth.
+    fn next_in_sub_tree_to_depth(&self, id: usize, top: usize, depth: &mut usize, max_depth: usize) -> Option<usize> {
+        if *depth < max_depth {
+            if let Some(child) = self.nodes[id].first_child.get() {
+                *depth += 1;
+                return Some(child);
+            }
+        }
+
+        let mut node = id;
+
+        while node != top {
+            if let Some(next) = self.nodes[node].next_sib.get() {
+                return Some(next);
+            }
+
+            node = self.nodes[node].parent.get().unwrap();
+            *depth -= 1;
+        }
+
+        None
+    }
+
+    /// Returns the id of the first node in the sub tree of the given id, in the same order as sub_tree, whose data matches the predicate.
+    pub fn find<F: FnMut(&T) -> bool>(&self, id: usize, mut predicate: F) -> Result<Option<usize>, TreeErr> {
+        self.valid_node(id)?;
+
+        let mut node = Some(id);
+
+        while let Some(node_id) = node {
+            if predicate(self.data_of(node_id).unwrap()) {
+                return Ok(Some(node_id));
+            }
+
+            node = self.next_in_sub_tree(node_id, id);
+        }
+
+        Ok(None)
+    }
+
+    /// Returns a list of all of the nodes in the sub tree of the given id, in the same order as sub_tree, whose data matches the predicate.
+    pub fn find_all<F: FnMut(&T) -> bool>(&self, id: usize, mut predicate: F) -> Result<Vec<usize>, TreeErr> {
+        self.valid_node(id)?;
+
+        let mut ids = Vec::new();
+        let mut node = Some(id);
+
+        while let Some(node_id) = node {
+            if predicate(self.data_of(node_id).unwrap()) {
+                ids.push(node_id);
+            }
+
+            node = self.next_in_sub_tree(node_id, id);
+        }
+
+        Ok(ids)
+    }
+
+    /// Returns the number of nodes in the sub tree of the given id whose data matches the predicate.
+    pub fn count_where<F: FnMut(&T) -> bool>(&self, id: usize, mut predicate: F) -> Result<usize, TreeErr> {
+        self.valid_node(id)?;
+
+        let mut count = 0;
+        let mut node = Some(id);
+
+        while let Some(node_id) = node {
+            if predicate(self.data_of(node_id).unwrap()) {
+                count += 1;
+            }
+
+            node = self.next_in_sub_tree(node_id, id);
+        }
+
+        Ok(count)
+    }
+
+    //the results of the finished children of every node still being folded are kept on one stack, along with where each node's children start.
+    fn fold_helper<R, F: FnMut(&T, Vec<R>) -> R>(&self, id: usize, f: &mut F) -> R {
+        let mut results = Vec::new();
+        let mut starts = Vec::new();
+
+        self.walk_sub_tree(id, |node_id, visit| match visit {
+            Visit::Enter => starts.push(results.len()),
+            Visit::Leave => {
+                let children = results.split_off(starts.pop().unwrap());
+                results.push(f(self.data_of(node_id).unwrap(), children));
+            }
+        });
+
+        results.pop().unwrap()
+    }
+
+    /// Combines the sub tree of the given id into a single value from the bottom up, calling f with the data of each node along with the results
+    /// of calling f on each of its children in order.
+    pub fn fold<R, F: FnMut(&T, Vec<R>) -> R>(&self, id: usize, mut f: F) -> Result<R, TreeErr> {
+        self.valid_node(id)?;
+
+        Ok(self.fold_helper(id, &mut f))
+    }
+
+    /// Returns a tree with the same shape and ids as this one, with the data of each node changed by f. The nodes are not visited in any particular order.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Tree<U> {
+        Tree {
+            nodes: self.nodes.to_vec(),
+            data: self.data.iter().map(f).collect(),
+            owners: self.owners.to_vec(),
+            free: self.free,
+            root: self.root,
+            len: self.len,
+        }
+    }
+
+    //keeps a stack of the new parent for the children of each node being gone through, which is the node's own copy or the parent it would have had if it was left out.
+    fn filter_map_helper<U, F: FnMut(&T) -> Option<U>>(&self, id: usize, new: &mut Tree<U>, tops: &mut Vec<usize>, f: &mut F) {
+        let mut parents: Vec<Option<usize>> = vec![None];
+
+        self.walk_sub_tree(id, |node_id, visit| {
+            if visit == Visit::Leave {
+                parents.pop();
+                return
+            }
+
+            let mut parent = *parents.last().unwrap();
+
+            if let Some(data) = f(self.data_of(node_id).unwrap()) {
+                let new_id = new.get_node(data);
+
+                match parent {
+                    Some(new_parent_id) => new.append_child(new_parent_id, new_id),
+                    None                => tops.push(new_id),
+                }
+
+                parent = Some(new_id);
+            }
+
+            parents.push(parent);
+        });
+    }
+
+    /// Returns a new tree with the data of each node changed by f, where nodes that f returns None for are left out and their children take their place.
+    /// Fails with MultipleRoots if the root is left out and more than one of its descendants would take its place.
+    pub fn filter_map<U, F: FnMut(&T) -> Option<U>>(&self, mut f: F) -> Result<Tree<U>, TreeErr> {
+        let mut new = Tree::new();
+
+        if let Some(root) = self.root {
+            let mut tops = Vec::new();
+            self.filter_map_helper(root, &mut new, &mut tops, &mut f);
+
+            if tops.len() > 1 { return Err(MultipleRoots) }
+            new.root = tops.pop();
+        }
+
+        Ok(new)
+    }
+
+    //the rows of the nodes that haven't been left yet are kept on a stack, so their right numbers can be filled in once they are.
+    fn to_nested_set_helper<'a>(&'a self, id: usize, rows: &mut Vec<(usize, usize, &'a T)>) {
+        let mut count = 0;
+        let mut open = Vec::new();
+
+        self.walk_sub_tree(id, |node_id, visit| {
+            count += 1;
+
+            match visit {
+                Visit::Enter => {
+                    open.push(rows.len());
+                    rows.push((count, 0, self.data_of(node_id).unwrap()));
+                }
+                Visit::Leave => rows[open.pop().unwrap()].1 = count,
+            }
+        });
+    }
+
+    /// Returns nested set rows of the left number, right number and data of every node in the tree, in the same order as sub_tree.
+    /// The numbers start from one at the left of the root and count up each time a node is entered or left.
+    pub fn to_nested_set(&self) -> Vec<(usize, usize, &T)> {
+        let mut rows = Vec::with_capacity(self.len);
+
+        if let Some(root) = self.root {
+            self.to_nested_set_helper(root, &mut rows);
+        }
+
+        rows
+    }
+
+    /// Returns closure table rows of the ancestor id, descendant id and the number of steps between them, for every node paired with itself and each
+    /// of its ancestors. The descendants are in the same order as sub_tree, each followed by its ancestors going up to the root.
+    pub fn to_closure_table(&self) -> Vec<(usize, usize, usize)> {
+        let mut rows = Vec::new();
+
+        if let Some(root) = self.root {
+            for id in self.sub_tree(root).unwrap() {
+                for (depth, ancestor) in self.path_to_root(id).unwrap().into_iter().enumerate() {
+                    rows.push((ancestor, id, depth));
+                }
+            }
+        }
+
+        rows
+    }
+
+    /// Writes each node on its own line made by render_line, indented by four spaces for each level below the root. The opposite of from_indented.
+    pub fn to_indented<W: std::fmt::Write, D: std::fmt::Display, F: FnMut(&T) -> D>(&self, write: &mut W, mut render_line: F) -> std::fmt::Result {
+        if let Some(root) = self.root {
+            for node in self.sub_tree_info(root).unwrap() {
+                writeln!(write, "{:indent$}{}", "", render_line(self.data_of(node.id).unwrap()), indent = node.depth * 4)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn to_html_helper<D: std::fmt::Display, F: FnMut(&T) -> D>(&self, id: usize, html: &mut String, render: &mut F) {
+        self.walk_sub_tree(id, |node_id, visit| {
+            let has_children = self.nodes[node_id].first_child.get().is_some();
+
+            if visit == Visit::Leave {
+                if has_children { html.push_str("</ul>") }
+                html.push_str("</li>");
+                return
+            }
+
+            html.push_str("<li>");
+            for c in render(self.data_of(node_id).unwrap()).to_string().chars() {
+                match c {
+                    '&' => html.push_str("&amp;"),
+                    '<' => html.push_str("&lt;"),
+                    '>' => html.push_str("&gt;"),
+                    '"' => html.push_str("&quot;"),
+                    '\'' => html.push_str("&#39;"),
+                    _ => html.push(c),
+                }
+            }
+
+            if has_children { html.push_str("<ul>") }
+        });
+    }
+
+    /// Returns the sub tree of the node as nested html lists, where each node is a list item holding the text made by render and a list of its children.
+    /// The text is escaped so it can't add markup of its own.
+    pub fn to_html<D: std::fmt::Display, F: FnMut(&T) -> D>(&self, id: usize, mut render: F) -> Result<String, TreeErr> {
+        self.valid_node(id)?;
+
+        let mut html = String::from("<ul>");
+        self.to_html_helper(id, &mut html, &mut render);
+        html.push_str("</ul>");
+
+        Ok(html)
+    }
+
+    /// Returns a list of the parent id, child id and child data of every edge in the tree, in the same order as sub_tree.
+    pub fn to_edges(&self) -> Vec<(usize, usize, &T)> {
+        match self.root {
+            Some(root) => self.descendants_of(root).unwrap().into_iter()
+                .map(|id| (self.nodes[id].parent.get().unwrap(), id, self.data_of(id).unwrap()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns a list of the id, parent id and data of every node in the tree, in the same order as sub_tree.
+    pub fn to_parent_list(&self) -> Vec<(usize, Option<usize>, &T)> {
+        match self.root {
+            Some(root) => self.sub_tree(root).unwrap().into_iter()
+                .map(|id| (id, self.nodes[id].parent.get(), self.data_of(id).unwrap()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Compares the sub trees of the given id in this tree and other id in the other tree, node by node in the same order as sub_tree.
+    fn sub_tree_eq_by<U, F: FnMut(&T, &U) -> bool>(&self, id: usize, other: &Tree<U>, other_id: usize, mut eq: F) -> bool {
+        let mut node = Some(id);
+        let mut other_node = Some(other_id);
+
+        while let (Some(node_id), Some(other_node_id)) = (node, other_node) {
+            let (a, b) = (&self.nodes[node_id], &other.nodes[other_node_id]);
+
+            if a.first_child.get().is_some() != b.first_child.get().is_some() { return false }
+            if node_id != id && a.next_sib.get().is_some() != b.next_sib.get().is_some() { return false }
+            if !eq(self.data_of(node_id).unwrap(), other.data_of(other_node_id).unwrap()) { return false }
+
+            node = self.next_in_sub_tree(node_id, id);
+            other_node = other.next_in_sub_tree(other_node_id, other_id);
+        }
+
+        node.is_none() && other_node.is_none()
+    }
+}
+
+//sub_tree_eq_by only compares against trees in the global allocator.
+impl<T> Tree<T> {
+    /// Returns whether the sub trees of the two given ids have the same shape, ignoring the data of the nodes.
+    pub fn is_isomorphic(&self, a: usize, b: usize) -> Result<bool, TreeErr> {
+        self.is_isomorphic_by(a, b, |_, _| true)
+    }
+
+    /// Returns whether the sub trees of the two given ids have the same shape, with eq returning true for the data of each pair of nodes in the same place.
+    pub fn is_isomorphic_by<F: FnMut(&T, &T) -> bool>(&self, a: usize, b: usize, eq: F) -> Result<bool, TreeErr> {
+        self.valid_node(a)?;
+        self.valid_node(b)?;
+
+        Ok(self.sub_tree_eq_by(a, self, b, eq))
+    }
+}
+
+impl_in_any_allocator! {
+    //checks the pairs of nodes and pattern nodes in the same place from a stack, so deep patterns don't use up the call stack.
+    fn pattern_matches<P, F: FnMut(&T, &P) -> bool>(&self, id: usize, pattern: &Tree<P>, pattern_id: usize, eq: &mut F) -> bool {
+        let mut pairs = vec![(id, pattern_id)];
+
+        while let Some((id, pattern_id)) = pairs.pop() {
+            if !eq(self.data_of(id).unwrap(), pattern.data_of(pattern_id).unwrap()) { return false }
+
+            let mut pattern_child = pattern.nodes[pattern_id].last_child.get();
+            if pattern_child.is_none() { continue }
+
+            //the children are pushed last first so they are checked in order.
+            let mut child = self.nodes[id].last_child.get();
+
+            while let (Some(child_id), Some(pattern_child_id)) = (child, pattern_child) {
+                pairs.push((child_id, pattern_child_id));
+
+                child = self.nodes[child_id].prev_sib.get();
+                pattern_child = pattern.nodes[pattern_child_id].prev_sib.get();
+            }
+
+            if child.is_some() || pattern_child.is_some() { return false }
+        }
+
+        true
+    }
+
+    /// Returns a list of the nodes in the sub tree of the given id, in the same order as sub_tree, where the pattern tree matches with eq returning true
+    /// for the data of each pair of nodes in the same place. Nodes with children only match pattern nodes with the same number of children, but leaves
+    /// of the pattern match nodes with any number of children, so a leaf that eq always accepts works as a wildcard.
+    pub fn find_pattern_by<P, F: FnMut(&T, &P) -> bool>(&self, id: usize, pattern: &Tree<P>, mut eq: F) -> Result<Vec<usize>, TreeErr> {
+        self.valid_node(id)?;
+
+        let mut ids = Vec::new();
+
+        if let Some(pattern_root) = pattern.root {
+            let mut node = Some(id);
+
+            while let Some(node_id) = node {
+                if self.pattern_matches(node_id, pattern, pattern_root, &mut eq) {
+                    ids.push(node_id);
+                }
+
+                node = self.next_in_sub_tree(node_id, id);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    fn valid_attach(&self, in_position: &Position, node: usize) -> Result<(), TreeErr> {
         match in_position {
-            FirstChild    | LastChild     => self.valid_node(node)?,
-            SiblingBefore | SiblingAfter  => self.valid_sib(node)?
+            FirstChild    | LastChild     => self.valid_node(node),
+            SiblingBefore | SiblingAfter  => self.valid_sib(node)
         }
+    }
+
+    /// Creates a new node containing the data provide and attaches it to the node provide in the position of in_position.
+    pub fn new_node(&mut self, data: T, in_position: Position, node: usize) -> Result<usize, TreeErr> {
+        self.valid_attach(&in_position, node)?;
 
         let new = self.get_node(data);
         self.attach(new, in_position, node);
@@ -431,14 +1812,61 @@ impl<T> Tree<T> {
         Ok(new)
     }
 
+    /// Creates a new node for each of the items provided and attaches them as a run of siblings to the node provided in the position of in_position.
+    /// The new nodes keep the order of the items, returning their ids in that order.
+    pub fn new_nodes<I: IntoIterator<Item = T>>(&mut self, items: I, in_position: Position, node: usize) -> Result<Vec<usize>, TreeErr> {
+        self.valid_attach(&in_position, node)?;
+
+        let mut items = items.into_iter();
+        let mut ids = Vec::with_capacity(items.size_hint().0);
+
+        if let Some(data) = items.next() {
+            let mut prev = self.get_node(data);
+            self.attach(prev, in_position, node);
+            ids.push(prev);
+
+            //every node after the first is placed directly after the previous one.
+            for data in items {
+                let new = self.get_node(data);
+                self.add_sibling_after(prev, new);
+                ids.push(new);
+                prev = new;
+            }
+        }
+
+        Ok(ids)
+    }
+
     /// Removes a node from the tree along with all of its descendants.
     pub fn remove(&mut self, id: usize) -> Result<(), TreeErr> {
         self.valid_node(id)?;
 
         self.decouple(id);
+        if self.root == Some(id) {
+            self.root = None;
+        }
+
+        //frees the sub tree from the bottom up, so every node is freed after its children and its links are only needed until then.
+        let mut node = id;
+
+        loop {
+            while let Some(child) = self.nodes[node].first_child.get() {
+                node = child;
+            }
 
-        for child in self.descendants_of(id).unwrap() {
-            self.push_free(child);
+            if node == id { break }
+
+            let parent = self.nodes[node].parent.get().unwrap();
+            let next = self.nodes[node].next_sib.get();
+            self.push_free(node);
+
+            node = match next {
+                Some(next) => next,
+                None => {
+                    self.nodes[parent].first_child = Link::NONE;
+                    parent
+                }
+            };
         }
 
         self.push_free(id);
@@ -446,18 +1874,49 @@ impl<T> Tree<T> {
         Ok(())
     }
 
+    /// Removes each of the given nodes along with all of their descendants. Ids that are repeated or are descendants of other given ids are skipped,
+    /// since they have already been removed. Fails with InvalidId before removing anything if an id isn't a node.
+    pub fn remove_many(&mut self, ids: &[usize]) -> Result<(), TreeErr> {
+        for &id in ids {
+            self.valid_node(id)?;
+        }
+
+        //removing a node frees its descendants without giving out their slots again, so they just stop being valid.
+        for &id in ids {
+            if self.valid_node(id).is_ok() {
+                self.remove(id).unwrap();
+            }
+        }
+
+        Ok(())
+    }
+
     /// Returns a reference to the data contained by the provided id.
     pub fn data_at(&self, id: usize) -> Result<&T, TreeErr>{
         self.valid_node(id)?;
 
-        Ok(self.nodes[id].data.as_ref().unwrap())
+        Ok(self.data_of(id).unwrap())
     }
 
     /// Returns a mutable reference to the data contained by the provided id.
     pub fn data_at_mut(&mut self, id: usize) -> Result<&mut T, TreeErr>{
         self.valid_node(id)?;
 
-        Ok(self.nodes[id].data.as_mut().unwrap())
+        Ok(self.data_of_mut(id).unwrap())
+    }
+
+    /// Returns a mutable reference to the data of each of the provided ids at the same time, in the same order.
+    /// Fails with InvalidId if any of the ids isn't a node and OverlappingIds if an id is given more than once.
+    pub fn data_at_many_mut<const N: usize>(&mut self, ids: [usize; N]) -> Result<[&mut T; N], TreeErr> {
+        let mut indices = [0; N];
+        for (index, &id) in indices.iter_mut().zip(&ids) {
+            *index = match self.nodes.get(id) {
+                Some(Slot::Occupied(_, index)) => widen(*index),
+                _ => return Err(InvalidId),
+            };
+        }
+
+        self.data.get_disjoint_mut(indices).map_err(|_| OverlappingIds)
     }
 
     /// Returns the current root of the tree.
@@ -479,10 +1938,59 @@ impl<T> Tree<T> {
     /// Set the Node with the given id to the root of the tree, removing the rest of the tree.
     pub fn make_root(&mut self, id: usize) -> Result<(), TreeErr> {
         self.valid_node(id)?;
+        if self.root == Some(id) { return Ok(()) }
 
         self.decouple(id);
         self.remove(self.root.unwrap()).unwrap();
         self.root = Some(id);
+        self.fix_depths(id);
+
+        Ok(())
+    }
+
+    /// Set the Node with the given id to the root of the tree, keeping the rest of the tree by reversing the relations between the node and the old root.
+    /// Each former parent along the way becomes the last child of the node that was its child.
+    pub fn reroot(&mut self, id: usize) -> Result<(), TreeErr> {
+        self.valid_node(id)?;
+
+        //the links along the way are flipped first and the sizes and depths fixed once at the end, so rerooting a deep chain
+        //doesn't walk the moved nodes again at every step.
+        let mut node = id;
+        let mut parent = self.nodes[id].parent.get();
+        self.unlink(id);
+
+        while let Some(parent_id) = parent {
+            parent = self.nodes[parent_id].parent.get();
+            self.unlink(parent_id);
+            self.link_last_child(node, parent_id);
+            node = parent_id;
+        }
+
+        //only the nodes between the old root and the new one change size, the old root is now the deepest of them.
+        #[cfg(feature = "subtree_sizes")]
+        {
+            let mut current = Some(node);
+            while let Some(current_id) = current {
+                let mut size = 1;
+                let mut child = self.nodes[current_id].first_child.get();
+                while let Some(child_id) = child {
+                    size += self.nodes[child_id].size;
+                    child = self.nodes[child_id].next_sib.get();
+                }
+                self.nodes[current_id].size = size;
+                current = self.nodes[current_id].parent.get();
+            }
+        }
+
+        self.root = Some(id);
+        let mut current = Some(id);
+        while let Some(current_id) = current {
+            self.nodes[current_id].depth = match self.nodes[current_id].parent.get() {
+                Some(parent_id) => self.nodes[parent_id].depth + 1,
+                None => 0,
+            };
+            current = self.next_in_sub_tree(current_id, id);
+        }
 
         Ok(())
     }
@@ -491,43 +1999,135 @@ impl<T> Tree<T> {
     pub fn parent_of(&self, id: usize) -> Result<Option<usize>, TreeErr>{
         self.valid_node(id)?;
 
-        Ok(self.nodes[id].parent)
+        Ok(self.nodes[id].parent.get())
     }
 
     /// Returns the next sibling id of the given id.
     pub fn next_sib_of(&self, id: usize) -> Result<Option<usize>, TreeErr>{
         self.valid_node(id)?;
 
-        Ok(self.nodes[id].next_sib)
+        Ok(self.nodes[id].next_sib.get())
     }
 
     /// Returns the previous sibling id of the given id.
     pub fn prev_sib_of(&self, id: usize) -> Result<Option<usize>, TreeErr>{
         self.valid_node(id)?;
 
-        Ok(self.nodes[id].prev_sib)
+        Ok(self.nodes[id].prev_sib.get())
     }
 
     /// Returns the first child id of the given id.
     pub fn first_child_of(&self, id: usize) -> Result<Option<usize>, TreeErr>{
         self.valid_node(id)?;
 
-        Ok(self.nodes[id].first_child)
+        Ok(self.nodes[id].first_child.get())
     }
 
     /// Returns the last child id of the given id.
     pub fn last_child_of(&self, id: usize) -> Result<Option<usize>, TreeErr>{
         self.valid_node(id)?;
 
-        Ok(self.nodes[id].last_child)
+        Ok(self.nodes[id].last_child.get())
+    }
+
+    /// Returns the position of the given id among its siblings, starting from zero.
+    pub fn sibling_index(&self, id: usize) -> Result<usize, TreeErr>{
+        self.valid_node(id)?;
+
+        let mut index = 0;
+        let mut prev = self.nodes[id].prev_sib.get();
+
+        while let Some(prev_id) = prev {
+            index += 1;
+            prev = self.nodes[prev_id].prev_sib.get();
+        }
+
+        Ok(index)
+    }
+
+    /// Returns the id of the child of the given id at the index provided, starting from zero.
+    pub fn nth_child(&self, id: usize, n: usize) -> Result<Option<usize>, TreeErr>{
+        self.valid_node(id)?;
+
+        let mut child = self.nodes[id].first_child.get();
+
+        for _ in 0..n {
+            match child {
+                Some(child_id) => child = self.nodes[child_id].next_sib.get(),
+                None           => break,
+            }
+        }
+
+        Ok(child)
+    }
+
+    /// Returns the id of the node after the given id going through the whole tree in the same order as sub_tree.
+    pub fn next_node(&self, id: usize) -> Result<Option<usize>, TreeErr>{
+        self.valid_node(id)?;
+
+        Ok(self.next_in_sub_tree(id, self.root.unwrap()))
+    }
+
+    /// Returns the id of the node before the given id going through the whole tree in the same order as sub_tree.
+    pub fn prev_node(&self, id: usize) -> Result<Option<usize>, TreeErr>{
+        self.valid_node(id)?;
+
+        if let Some(mut node) = self.nodes[id].prev_sib.get() {
+            while let Some(last) = self.nodes[node].last_child.get() {
+                node = last;
+            }
+
+            return Ok(Some(node));
+        }
+
+        Ok(self.nodes[id].parent.get())
+    }
+
+    /// Returns a list of the nodes from the first id up to and including the last id, going through the whole tree in the same order as sub_tree.
+    /// If the last id comes before the first id the list is empty.
+    pub fn range(&self, first: usize, last: usize) -> Result<Vec<usize>, TreeErr>{
+        if self.path_of(first)? > self.path_of(last)? {
+            return Ok(Vec::new());
+        }
+
+        let mut ids = vec![first];
+        let mut node = first;
+
+        while node != last {
+            node = self.next_in_sub_tree(node, self.root.unwrap()).unwrap();
+            ids.push(node);
+        }
+
+        Ok(ids)
+    }
+
+    /// Returns the id of the node found by starting at the root and going to the child at each of the indexes in the path.
+    pub fn node_at_path(&self, path: &[usize]) -> Result<usize, TreeErr>{
+        let mut node = self.root.ok_or(InvalidId)?;
+
+        for &index in path {
+            node = self.nth_child(node, index)?.ok_or(OutOfRange)?;
+        }
+
+        Ok(node)
+    }
+
+    /// Returns the indexes of the children to go through starting from the root to reach the given id, the opposite of node_at_path.
+    pub fn path_of(&self, id: usize) -> Result<Vec<usize>, TreeErr>{
+        let mut path = self.path_to_root(id)?;
+        path.pop();
+
+        Ok(path.into_iter().rev().map(|node| self.sibling_index(node).unwrap()).collect())
     }
 
     fn valid_move(&self, moving: usize, new_place: usize) -> Result<(), TreeErr> {
         self.valid_node(moving)?;
         self.valid_node(new_place)?;
 
-        for id in self.sub_tree(moving).unwrap() {
-            if id == new_place {return Err(CantMoveIntoChild)}
+        let mut current = Some(new_place);
+        while let Some(id) = current {
+            if id == moving {return Err(CantMoveIntoChild)}
+            current = self.nodes[id].parent.get();
         }
 
         Ok(())
@@ -542,134 +2142,493 @@ impl<T> Tree<T> {
 
         Ok(())
     }
+
+    /// Creates a new node containing the data provided in place of the given range of the parents children, moving those children to be its children.
+    /// Returns the new node's id.
+    pub fn group_children(&mut self, parent: usize, range: std::ops::Range<usize>, data: T) -> Result<usize, TreeErr> {
+        self.valid_node(parent)?;
+        if range.start > range.end { return Err(OutOfRange) }
+
+        let mut first = self.nodes[parent].first_child.get();
+        let mut count = 0;
+
+        while count < range.start {
+            first = self.nodes[first.ok_or(OutOfRange)?].next_sib.get();
+            count += 1;
+        }
+
+        let mut last = first;
+        while count < range.end {
+            last = self.nodes[last.ok_or(OutOfRange)?].next_sib.get();
+            count += 1;
+        }
+
+        let new = self.get_node(data);
+
+        if let Some(first_id) = first {
+            self.add_sibling_before(first_id, new);
+        }
+        else {
+            self.append_child(parent, new);
+        }
+
+        while self.nodes[new].next_sib.get() != last {
+            let child = self.nodes[new].next_sib.get().unwrap();
+            self.decouple(child);
+            self.append_child(new, child);
+        }
+
+        Ok(new)
+    }
+
+    /// Moves the given node into the position of its parent, the parent becomes the last child of the node and keeps the rest of its children.
+    /// Promoting a child of the root makes it the new root.
+    pub fn promote(&mut self, id: usize) -> Result<(), TreeErr> {
+        self.valid_sib(id)?;
+
+        let parent = self.nodes[id].parent.get().unwrap();
+        self.decouple(id);
+
+        if self.nodes[parent].parent.get().is_some() {
+            self.add_sibling_before(parent, id);
+            self.decouple(parent);
+        }
+        else {
+            self.root = Some(id);
+            self.fix_depths(id);
+        }
+
+        self.append_child(id, parent);
+
+        Ok(())
+    }
 }
 
-impl<T: Clone> Tree<T> {
-    fn clone_children(&mut self, old_parent: usize, new_parent: usize){
-        let mut old_child = self.nodes[old_parent].first_child;
+//moving nodes between trees only works for trees in the global allocator.
+impl<T> Tree<T> {
+    /// Moves the children of old_parent in the from tree to be the children of new_parent in the to tree, freeing the old ids.
+    /// The next child to move at each level is kept on a stack, so deep trees don't use up the call stack.
+    fn transfer_children(from: &mut Tree<T>, old_parent: usize, to: &mut Tree<T>, new_parent: usize) {
+        let mut levels = vec![(from.nodes[old_parent].first_child.get(), new_parent)];
+
+        while let Some((old_child, new_parent)) = levels.last_mut() {
+            let old_child_id = match *old_child {
+                Some(old_child_id) => old_child_id,
+                None => {
+                    levels.pop();
+                    continue
+                }
+            };
+            let new_parent = *new_parent;
+            *old_child = from.nodes[old_child_id].next_sib.get();
+
+            let new_child = to.get_node(from.take_data(old_child_id).unwrap());
+            to.append_child(new_parent, new_child);
+
+            let first_grandchild = from.nodes[old_child_id].first_child.get();
+            from.push_free(old_child_id);
+            levels.push((first_grandchild, new_child));
+        }
+    }
 
-        while let Some(old_child_id) = old_child {
-            let new_child = self.get_node(self.nodes[old_child_id].data.clone().unwrap());
-            self.append_child(new_parent, new_child);
-            self.clone_children(old_child_id, new_child);
-            old_child = self.nodes[old_child_id].next_sib;
+    /// Moves the given node and its descendants out of the tree and into a tree of their own.
+    fn split_off(&mut self, id: usize) -> Tree<T> {
+        self.decouple(id);
+        if self.root == Some(id) {
+            self.root = None;
         }
+
+        let mut tree = Tree::new_with_root(self.take_data(id).unwrap());
+        Tree::transfer_children(self, id, &mut tree, 0);
+        self.push_free(id);
+
+        tree
     }
 
-    fn clone_node(&mut self, id: usize) -> usize {
-        let new = self.get_node(self.nodes[id].data.clone().unwrap());
-        self.clone_children(id, new);
-        new
+    /// Moves all of the nodes of the other tree into this one, returning the unattached id of the other trees root.
+    fn graft(&mut self, mut other: Tree<T>) -> Option<usize> {
+        let root = other.root?;
+
+        let new = self.get_node(other.take_data(root).unwrap());
+        Tree::transfer_children(&mut other, root, self, new);
+
+        Some(new)
     }
 
-    /// Clones the given node to be attached to the given node in the position of in_position. Returning the copies new node id.
-    pub fn clone_to(&mut self, cloning: usize, in_position: Position, node: usize) -> Result<usize, TreeErr> {
-        self.valid_node(cloning)?;
-        self.valid_node(node)?;
+    /// Replaces the given node and its descendants with the contents of the other tree, returning the replaced nodes as a tree of their own.
+    /// If the other tree is empty the node is removed without a replacement.
+    pub fn replace_subtree(&mut self, id: usize, other: Tree<T>) -> Result<Tree<T>, TreeErr> {
+        self.valid_node(id)?;
 
-        let new = self.clone_node(cloning);
-        self.attach(new, in_position, node);
+        if let Some(new) = self.graft(other) {
+            if self.nodes[id].parent.get().is_some() {
+                self.add_sibling_before(id, new);
+            }
+            else {
+                self.root = Some(new);
+            }
+        }
 
-        Ok(new)
+        Ok(self.split_off(id))
     }
 }
 
-/// The u8 iterator for all of the data in the tree.
-pub struct TreeIter<'a, T>{
-    tree: &'a Tree<T>,
-    nodes_iter: std::vec::IntoIter<NodeInfo>,
-    data_iter: Box<dyn std::iter::Iterator<Item = u8> + 'a>,
-}
+impl_in_any_allocator! {
+    //the number of children of the node, kept up to date as children are attached and decoupled.
+    fn count_children(&self, id: usize) -> usize {
+        widen(self.nodes[id].child_count)
+    }
 
-impl<'a, T> TreeIter<'a, T> {
-    fn new(tree: &'a Tree<T>) -> Self {
-        let nodes = if let Some(root) = tree.get_root(){
-            tree.sub_tree_info(root).unwrap()
+    //gives the nodes in order the ids 0, 1, 2.. with their data in the same order, dropping every other slot. Returns the new id of each old id.
+    fn relabel(&mut self, order: &[usize]) -> Vec<Option<usize>> {
+        let mut remap = vec![None; self.nodes.len()];
+        for (new, &old) in order.iter().enumerate() {
+            remap[old] = Some(new);
         }
-        else {
-            Vec::new()
-        };
+        let relink = |link: Link| Link::new(link.get().map(|id| remap[id].unwrap()));
+
+        let mut nodes = Vec::with_capacity(order.len());
+        for (new, &old) in order.iter().enumerate() {
+            let node = *self.nodes[old];
+            nodes.push(Slot::Occupied(Node {
+                parent:      relink(node.parent),
+                prev_sib:    relink(node.prev_sib),
+                next_sib:    relink(node.next_sib),
+                first_child: relink(node.first_child),
+                last_child:  relink(node.last_child),
+                #[cfg(feature = "subtree_sizes")]
+                size:        node.size,
+                child_count: node.child_count,
+                depth:       node.depth,
+            }, narrow(new)));
+        }
+
+        let mut data: Vec<Option<T>> = self.data.drain(..).map(Some).collect();
+        for &old in order {
+            if let Slot::Occupied(_, index) = self.nodes[old] {
+                self.data.push(data[widen(index)].take().unwrap());
+            }
+        }
+
+        self.nodes.clear();
+        self.nodes.extend(nodes);
+        self.owners.clear();
+        self.owners.extend((0..order.len()).map(narrow));
+        self.root = self.root.map(|root| remap[root].unwrap());
+        self.free = None;
+
+        remap
+    }
 
-        let temp = tree.get_root().is_some().into_bytes_static();
+    //the number of slots needed to keep the listed ids. The ids come from outside the tree, so they can leave at most
+    //SLOTS_PER_LISTED_NODE slots for each listed node, otherwise a few nodes with huge ids could allocate any amount of memory.
+    fn listed_slot_count<I: Iterator<Item = usize>>(ids: I, node_count: usize) -> Result<usize, TreeErr> {
+        let bound = node_count.saturating_mul(SLOTS_PER_LISTED_NODE).max(MIN_LISTED_SLOTS);
+        let mut slot_count = 0;
 
-        TreeIter {
-            data_iter: Box::new(temp),
-            nodes_iter: nodes.into_iter(),
-            tree: tree,
+        for id in ids {
+            match id.checked_add(1) {
+                Some(slots) if slots <= bound => slot_count = slot_count.max(slots),
+                _ => return Err(OutOfRange),
+            }
         }
+
+        Ok(slot_count)
     }
-}
 
-impl<'a, T: IntoBytes<'a>> Iterator for TreeIter<'a, T> {
-    type Item = u8;
+    /// Replaces the children of the parent with the given ids in order.
+    fn relink_children(&mut self, parent: usize, children: &[usize]) {
+        self.nodes[parent].first_child = Link::new(children.first().copied());
+        self.nodes[parent].last_child  = Link::new(children.last().copied());
+        self.nodes[parent].child_count = narrow(children.len());
 
-    fn next(&mut self) -> Option<Self::Item>{
-        if let Some(value) = self.data_iter.next(){
-            Some(value)
+        for (i, &child) in children.iter().enumerate() {
+            self.nodes[child].prev_sib = Link::new(if i > 0 { Some(children[i - 1]) } else { None });
+            self.nodes[child].next_sib = Link::new(children.get(i + 1).copied());
         }
-        else {
-            if let Some(node) = self.nodes_iter.next(){
-                let data = Some(self.tree.data_at(node.id).unwrap());
-                let child_count = node.child_count as u32;
-                self.data_iter = Box::new(data.unwrap().into_bytes().chain(child_count.into_bytes_static()));
-                self.data_iter.next()
+    }
+
+    /// Sorts the children of every node in the tree by the key provided, so that trees with the same nodes end up in the same order.
+    /// Children with equal keys keep their current order.
+    pub fn canonicalize_by<K: Ord, F: FnMut(&T) -> K>(&mut self, mut key: F) {
+        if let Some(root) = self.root {
+            for id in self.sub_tree(root).unwrap() {
+                let mut children = self.children_of(id).unwrap();
+                if children.len() < 2 { continue }
+
+                children.sort_by_cached_key(|&child| key(self.data_of(child).unwrap()));
+                self.relink_children(id, &children);
             }
-            else { None }
         }
     }
+
+    /// Makes every descendant of the given node one of its direct children, in the same order as descendants_of.
+    pub fn flatten(&mut self, id: usize) -> Result<(), TreeErr> {
+        let descendants = self.descendants_of(id)?;
+
+        for &descendant in &descendants {
+            self.nodes[descendant].parent      = Link::some(id);
+            self.nodes[descendant].first_child = Link::NONE;
+            self.nodes[descendant].last_child  = Link::NONE;
+            #[cfg(feature = "subtree_sizes")]
+            {
+                self.nodes[descendant].size = 1;
+            }
+            self.nodes[descendant].child_count = 0;
+            self.nodes[descendant].depth       = self.nodes[id].depth + 1;
+        }
+
+        self.relink_children(id, &descendants);
+
+        Ok(())
+    }
 }
 
-impl<'a, A: IntoBytes<'a>> IntoBytes<'a> for Tree<A>{
+impl<T> Tree<T> {
+    /// Removes each of the given nodes along with their descendants, returning them as trees of their own in the same order as the ids.
+    /// None of the ids can be the same as or a descendant of another.
+    pub fn extract_subtrees(&mut self, ids: &[usize]) -> Result<Vec<Tree<T>>, TreeErr> {
+        let mut extracting = HashSet::with_capacity(ids.len());
+
+        for &id in ids {
+            self.valid_node(id)?;
+            if !extracting.insert(id) { return Err(OverlappingIds) }
+        }
+
+        for &id in ids {
+            let mut ancestor = self.nodes[id].parent.get();
+
+            while let Some(ancestor_id) = ancestor {
+                if extracting.contains(&ancestor_id) { return Err(OverlappingIds) }
+                ancestor = self.nodes[ancestor_id].parent.get();
+            }
+        }
 
-    fn into_bytes(&'a self) -> Box<dyn Iterator<Item = u8> + 'a> {
-        Box::new(TreeIter::new(self))
+        Ok(ids.iter().map(|&id| self.split_off(id)).collect())
+    }
+
+    //merges the pairs of nodes from a stack, where combining two nodes pushes the pairs of their children with the same key so deep trees don't use up the call stack.
+    fn merge_nodes<K, F, R>(&mut self, id: usize, other: &mut Tree<T>, other_id: usize, key: &F, resolve: &mut R)
+    where K: Eq + Hash, F: Fn(&T) -> K, R: FnMut(&mut T, T) -> MergeDecision
+    {
+        let mut merging = vec![(id, other_id)];
+
+        while let Some((id, other_id)) = merging.pop() {
+            let data = other.take_data(other_id).unwrap();
+
+            match resolve(self.data_of_mut(id).unwrap(), data) {
+                Keep    => {},
+                Replace => {
+                    while let Some(child) = self.nodes[id].first_child.get() {
+                        self.remove(child).unwrap();
+                    }
+
+                    Tree::transfer_children(other, other_id, self, id);
+                },
+                Combine => {
+                    //the pairs are merged in the order of the children.
+                    let start = merging.len();
+                    self.merge_children(id, other, other_id, key, &mut merging);
+                    merging[start..].reverse();
+                },
+            }
+        }
+    }
+
+    //adds the pairs of children of the two parents with the same key to merging, and moves the rest of the other parent's children over as the last children of parent.
+    fn merge_children<K, F>(&mut self, parent: usize, other: &mut Tree<T>, other_parent: usize, key: &F, merging: &mut Vec<(usize, usize)>)
+    where K: Eq + Hash, F: Fn(&T) -> K
+    {
+        let mut keys = HashMap::new();
+        let mut child = self.nodes[parent].first_child.get();
+
+        while let Some(child_id) = child {
+            keys.entry(key(self.data_of(child_id).unwrap())).or_insert(child_id);
+            child = self.nodes[child_id].next_sib.get();
+        }
+
+        let mut other_child = other.nodes[other_parent].first_child.get();
+
+        while let Some(other_child_id) = other_child {
+            other_child = other.nodes[other_child_id].next_sib.get();
+
+            match keys.entry(key(other.data_of(other_child_id).unwrap())) {
+                Entry::Occupied(entry) => {
+                    merging.push((*entry.get(), other_child_id));
+                }
+                Entry::Vacant(entry) => {
+                    let new = self.get_node(other.take_data(other_child_id).unwrap());
+                    self.append_child(parent, new);
+                    entry.insert(new);
+                    Tree::transfer_children(other, other_child_id, self, new);
+                }
+            }
+        }
+    }
+
+    /// Merges the other tree into this one like merging directories. The roots are combined, then children with the same key under combined parents
+    /// are combined as well keeping the data already in this tree, and the rest of the children are added as the last children of their parent.
+    pub fn merge_by_key<K: Eq + Hash, F: Fn(&T) -> K>(&mut self, other: Tree<T>, key: F) {
+        self.merge_by_key_with(other, key, |_, _| Combine);
+    }
+
+    /// Merges the other tree into this one the same as merge_by_key, except that whenever two nodes are combined resolve is given the data in this tree
+    /// along with the other node's data to keep, replace or combine them. The returned decision chooses what happens to the children of the two nodes.
+    pub fn merge_by_key_with<K, F, R>(&mut self, mut other: Tree<T>, key: F, mut resolve: R)
+    where K: Eq + Hash, F: Fn(&T) -> K, R: FnMut(&mut T, T) -> MergeDecision
+    {
+        if let Some(other_root) = other.root {
+            if let Some(root) = self.root {
+                self.merge_nodes(root, &mut other, other_root, &key, &mut resolve);
+            }
+            else {
+                *self = other;
+            }
+        }
     }
 }
 
-impl<A: FromBytes> Tree<A>{
-    fn from_bytes_helper<T: Iterator<Item = u8>>(&mut self, parent: usize, bytes: &mut T) -> Result<(), ByteErr>{
-        for _ in 0..(u32::from_bytes(bytes)?) as usize {
-            let child = self.get_node(A::from_bytes(bytes)?);
+impl<A, B> Tree<(A, B)> {
+    //the next pair of children to combine at each level is kept on a stack, so deep trees don't use up the call stack.
+    fn zip_helper(&mut self, parent: usize, a: &mut Tree<A>, a_parent: usize, b: &mut Tree<B>, b_parent: usize) {
+        let mut levels = vec![(parent, a.nodes[a_parent].first_child.get(), b.nodes[b_parent].first_child.get())];
+
+        while let Some((parent, a_child, b_child)) = levels.last_mut() {
+            let (a_id, b_id) = match (*a_child, *b_child) {
+                (Some(a_id), Some(b_id)) => (a_id, b_id),
+                _ => {
+                    levels.pop();
+                    continue
+                }
+            };
+            let parent = *parent;
+            *a_child = a.nodes[a_id].next_sib.get();
+            *b_child = b.nodes[b_id].next_sib.get();
+
+            let child = self.get_node((a.take_data(a_id).unwrap(), b.take_data(b_id).unwrap()));
             self.append_child(parent, child);
-            self.from_bytes_helper(child, bytes)?;
+            levels.push((child, a.nodes[a_id].first_child.get(), b.nodes[b_id].first_child.get()));
         }
+    }
 
-        Ok(())
+    /// Combines two trees with the same shape into one tree holding the data of both trees in each node.
+    /// Fails with ShapeMismatch if the trees have different shapes.
+    pub fn zip(mut a: Tree<A>, mut b: Tree<B>) -> Result<Tree<(A, B)>, TreeErr> {
+        match (a.root, b.root) {
+            (Some(a_root), Some(b_root)) => {
+                if !a.sub_tree_eq_by(a_root, &b, b_root, |_, _| true) { return Err(ShapeMismatch) }
+
+                let mut tree = Tree::new_with_root((a.take_data(a_root).unwrap(), b.take_data(b_root).unwrap()));
+                tree.zip_helper(0, &mut a, a_root, &mut b, b_root);
+
+                Ok(tree)
+            }
+            (None, None) => Ok(Tree::new()),
+            _            => Err(ShapeMismatch),
+        }
     }
+}
 
-    fn from_io_bytes_helper<T: Iterator<Item = Result<u8, std::io::Error>>>(&mut self, parent: usize, bytes: &mut T) -> Result<(), ByteErr>{
-        for _ in 0..(u32::from_io_bytes(bytes)?) as usize {
-            let child = self.get_node(A::from_io_bytes(bytes)?);
-            self.append_child(parent, child);
-            self.from_io_bytes_helper(child, bytes)?;
+impl<T: PartialEq> Tree<T> {
+    /// Returns whether the two trees have the same shape and the same data in the same places, ignoring the ids of the nodes.
+    pub fn structural_eq(a: &Tree<T>, b: &Tree<T>) -> bool {
+        if a.len != b.len { return false }
+
+        match (a.root, b.root) {
+            (Some(a_root), Some(b_root)) => a.sub_tree_eq_by(a_root, b, b_root, |a, b| a == b),
+            (None, None)                 => true,
+            _                            => false,
         }
+    }
 
-        Ok(())
+    /// Returns a list of the nodes in the sub tree of the given id where the pattern tree matches, the same as find_pattern_by comparing the data for equality.
+    pub fn find_pattern(&self, id: usize, pattern: &Tree<T>) -> Result<Vec<usize>, TreeErr> {
+        self.find_pattern_by(id, pattern, |a, b| a == b)
     }
 }
 
-impl<A: FromBytes> FromBytes for Tree<A>{
-    fn from_bytes<T: Iterator<Item = u8>>(bytes: &mut T) -> Result<Self, ByteErr>{
-        
-        if bool::from_bytes(bytes)? {
-            let mut tree = Tree::new_with_root(A::from_bytes(bytes)?);
-            tree.from_bytes_helper(0, bytes)?;
-            Ok(tree)
+impl<T: PartialEq> PartialEq for Tree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Tree::structural_eq(self, other)
+    }
+}
+
+impl<T: Eq> Eq for Tree<T> {}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Tree<T> {
+    /// Lists the data of each node with its number of children, in the same order as sub_tree.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut list = f.debug_list();
+
+        if let Some(root) = self.root {
+            for node in self.sub_tree_info(root).unwrap() {
+                list.entry(&(self.data_of(node.id).unwrap(), node.child_count));
+            }
         }
-        else {
-            Ok(Tree::new())
+
+        list.finish()
+    }
+}
+
+impl<T: Hash> Tree<T> {
+    /// Feeds the shape and data of the sub tree of the given id into the hasher, in the same order as sub_tree.
+    fn hash_sub_tree<H: Hasher>(&self, id: usize, state: &mut H) {
+        for node in self.sub_tree_info(id).unwrap() {
+            self.data_of(node.id).hash(state);
+            node.child_count.hash(state);
         }
     }
 
-    fn from_io_bytes<T: Iterator<Item = Result<u8, std::io::Error>>>(bytes: &mut T) -> Result<Self, ByteErr>{
+    /// Returns a hash of the shape and data of the sub tree of the given id, sub trees that are structurally equal have the same hash.
+    pub fn subtree_hash(&self, id: usize) -> Result<u64, TreeErr> {
+        self.valid_node(id)?;
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.hash_sub_tree(id, &mut hasher);
+
+        Ok(hasher.finish())
+    }
+}
+
+impl<T: Hash> Hash for Tree<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
 
-        if bool::from_io_bytes(bytes)? {
-            let mut tree = Tree::new_with_root(A::from_io_bytes(bytes)?);
-            tree.from_io_bytes_helper(0, bytes)?;
-            Ok(tree)
+        if let Some(root) = self.root {
+            self.hash_sub_tree(root, state);
         }
-        else {
-            Ok(Tree::new())
+    }
+}
+
+impl<T: Clone> Tree<T> {
+    fn clone_node(&mut self, id: usize) -> usize {
+        let new = self.get_node(self.data_of(id).unwrap().clone());
+
+        //the copies of the nodes on the way down to the current node, so each copy can find the copy of its parent.
+        let mut copies = vec![new];
+        let mut depth = 0;
+        let mut old = self.next_in_sub_tree_to_depth(id, id, &mut depth, usize::MAX);
+
+        while let Some(old_id) = old {
+            copies.truncate(depth);
+            let copy = self.get_node(self.data_of(old_id).unwrap().clone());
+            self.append_child(copies[depth - 1], copy);
+            copies.push(copy);
+
+            old = self.next_in_sub_tree_to_depth(old_id, id, &mut depth, usize::MAX);
         }
+
+        new
     }
-}
\ No newline at end of file
+
+    /// Clones the given node to be attached to the given node in the position of in_position. Returning the copies new node id.
+    pub fn clone_to(&mut self, cloning: usize, in_position: Position, node: usize) -> Result<usize, TreeErr> {
+        self.valid_node(cloning)?;
+        self.valid_node(node)?;
+
+        let new = self.clone_node(cloning);
+        self.attach(new, in_position, node);
+
+        Ok(new)
+    }
+}
@@ -0,0 +1,149 @@
+//! Proptest strategies for generating and shrinking trees, enabled by the proptest feature.
+
+use super::*;
+use proptest::arbitrary::Arbitrary;
+use proptest::collection::SizeRange;
+use proptest::strategy::{Strategy, ValueTree, NewTree};
+use proptest::test_runner::TestRunner;
+
+/// A strategy that makes trees with a node count in the size range and data from the data strategy.
+/// Each node after the root is added as the last child of a random earlier node.
+#[derive(Clone, Debug)]
+pub struct TreeStrategy<S> {
+    data: S,
+    size: SizeRange,
+}
+
+impl<S: Strategy> TreeStrategy<S> {
+    /// Creates a strategy from the strategy for the data of each node and the range of node counts.
+    pub fn new<R: Into<SizeRange>>(data: S, size: R) -> TreeStrategy<S> {
+        TreeStrategy {
+            data,
+            size: size.into(),
+        }
+    }
+}
+
+impl<S: Strategy> Strategy for TreeStrategy<S> {
+    type Tree = TreeValueTree<S::Tree>;
+    type Value = Tree<S::Value>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let len = (self.size.start()..self.size.end_excl()).new_tree(runner)?.current();
+        let mut values = Vec::with_capacity(len);
+        let mut parents = Vec::with_capacity(len);
+
+        for i in 0..len {
+            values.push(self.data.new_tree(runner)?);
+            parents.push(if i == 0 { None } else { Some((0..i).new_tree(runner)?.current()) });
+        }
+
+        Ok(TreeValueTree {
+            values,
+            parents,
+            removed: vec![false; len],
+            next_removal: 1,
+            next_data: 0,
+            last: None,
+        })
+    }
+}
+
+//the last change made while shrinking, so it can be undone.
+#[derive(Clone, Copy)]
+enum Shrink {
+    Remove(usize),
+    Data(usize),
+}
+
+/// The value tree of a TreeStrategy. It shrinks by first removing whole sub trees, starting with the ones closest to the root,
+/// and then shrinking the data of the nodes that are left.
+pub struct TreeValueTree<V> {
+    values: Vec<V>,
+    parents: Vec<Option<usize>>,
+    removed: Vec<bool>,
+    next_removal: usize,
+    next_data: usize,
+    last: Option<Shrink>,
+}
+
+impl<V> TreeValueTree<V> {
+    //a node is included if neither it nor any of its ancestors were removed, parents always come before their children.
+    fn included(&self) -> Vec<bool> {
+        let mut included = Vec::with_capacity(self.values.len());
+        for (i, parent) in self.parents.iter().enumerate() {
+            included.push(!self.removed[i] && parent.is_none_or(|parent| included[parent]));
+        }
+        included
+    }
+}
+
+impl<V: ValueTree> ValueTree for TreeValueTree<V> {
+    type Value = Tree<V::Value>;
+
+    fn current(&self) -> Tree<V::Value> {
+        let included = self.included();
+        let mut ids = vec![0; self.values.len()];
+        let mut tree = Tree::new();
+
+        for (i, value) in self.values.iter().enumerate() {
+            if !included[i] { continue }
+
+            ids[i] = tree.get_node(value.current());
+            match self.parents[i] {
+                Some(parent) => tree.append_child(ids[parent], ids[i]),
+                None => tree.root = Some(ids[i]),
+            }
+        }
+
+        tree
+    }
+
+    fn simplify(&mut self) -> bool {
+        let included = self.included();
+
+        while self.next_removal < self.values.len() {
+            let id = self.next_removal;
+            self.next_removal += 1;
+
+            if included[id] {
+                self.removed[id] = true;
+                self.last = Some(Shrink::Remove(id));
+                return true
+            }
+        }
+
+        while self.next_data < self.values.len() {
+            if included[self.next_data] && self.values[self.next_data].simplify() {
+                self.last = Some(Shrink::Data(self.next_data));
+                return true
+            }
+            self.next_data += 1;
+        }
+
+        false
+    }
+
+    fn complicate(&mut self) -> bool {
+        match self.last.take() {
+            Some(Shrink::Remove(id)) => {
+                self.removed[id] = false;
+                true
+            }
+            Some(Shrink::Data(id)) if self.values[id].complicate() => {
+                self.last = Some(Shrink::Data(id));
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Tree<T> {
+    type Parameters = (SizeRange, T::Parameters);
+    type Strategy = TreeStrategy<T::Strategy>;
+
+    fn arbitrary_with((size, args): Self::Parameters) -> Self::Strategy {
+        TreeStrategy::new(T::arbitrary_with(args), size)
+    }
+}
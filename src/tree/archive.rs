@@ -0,0 +1,213 @@
+//! rkyv support, enabled by the rkyv feature. Tree derives the rkyv traits, so an archived tree can be accessed straight from its
+//! bytes as an ArchivedTree and crawled without having to deserialize it first. ArchivedTree has these read only methods of a tree:
+//! - len
+//! - is_empty
+//! - get_root
+//! - contains
+//! - data_at
+//! - parent_of
+//! - next_sib_of
+//! - prev_sib_of
+//! - first_child_of
+//! - last_child_of
+//! - children_of
+//! - child_count
+//! - depth_of
+//! - is_leaf
+//! - is_root
+//! - is_ancestor_of
+//! - is_descendant_of
+//! - path_to_root
+//! - sub_tree
+//! - next_node
+//! - prev_node
+
+use super::*;
+use rkyv::Archive;
+use rkyv::option::ArchivedOption;
+
+//converts an archived id back into a native one.
+fn native(id: &ArchivedOption<rkyv::Archived<usize>>) -> Option<usize> {
+    id.as_ref().map(|id| id.to_native() as usize)
+}
+
+impl<T: Archive> ArchivedTree<T> {
+    fn archived_slot(&self, id: usize) -> Result<(&ArchivedNode, &T::Archived), TreeErr> {
+        match self.nodes.get(id) {
+            Some(ArchivedSlot::Occupied(node, index)) => Ok((node, &self.data[index.to_native() as usize])),
+            _ => Err(InvalidId),
+        }
+    }
+
+    fn archived_node(&self, id: usize) -> Result<&ArchivedNode, TreeErr> {
+        Ok(self.archived_slot(id)?.0)
+    }
+
+    /// Returns the number of nodes in the tree.
+    pub fn len(&self) -> usize {
+        self.len.to_native() as usize
+    }
+
+    /// Returns true if there are no nodes in the tree.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the id of the root.
+    pub fn get_root(&self) -> Option<usize> {
+        native(&self.root)
+    }
+
+    /// Returns true if the id points to a node in the tree.
+    pub fn contains(&self, id: usize) -> bool {
+        self.archived_node(id).is_ok()
+    }
+
+    /// Returns a reference to the archived data at the id.
+    pub fn data_at(&self, id: usize) -> Result<&T::Archived, TreeErr> {
+        Ok(self.archived_slot(id)?.1)
+    }
+
+    /// Returns the id of the parent of the node.
+    pub fn parent_of(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        Ok(self.archived_node(id)?.parent.get())
+    }
+
+    /// Returns the id of the next sibling of the node.
+    pub fn next_sib_of(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        Ok(self.archived_node(id)?.next_sib.get())
+    }
+
+    /// Returns the id of the previous sibling of the node.
+    pub fn prev_sib_of(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        Ok(self.archived_node(id)?.prev_sib.get())
+    }
+
+    /// Returns the id of the first child of the node.
+    pub fn first_child_of(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        Ok(self.archived_node(id)?.first_child.get())
+    }
+
+    /// Returns the id of the last child of the node.
+    pub fn last_child_of(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        Ok(self.archived_node(id)?.last_child.get())
+    }
+
+    /// Returns the ids of the children of the node.
+    pub fn children_of(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
+        let mut children = Vec::new();
+        let mut child = self.first_child_of(id)?;
+
+        while let Some(child_id) = child {
+            children.push(child_id);
+            child = self.next_sib_of(child_id)?;
+        }
+
+        Ok(children)
+    }
+
+    /// Returns the number of children the node has, without going through them.
+    pub fn child_count(&self, id: usize) -> Result<usize, TreeErr> {
+        Ok(self.archived_node(id)?.child_count.to_native() as usize)
+    }
+
+    /// Returns the number of ancestors the node has, the root has a depth of zero.
+    pub fn depth_of(&self, id: usize) -> Result<usize, TreeErr> {
+        Ok(self.archived_node(id)?.depth.to_native() as usize)
+    }
+
+    /// Returns whether the node has no children.
+    pub fn is_leaf(&self, id: usize) -> Result<bool, TreeErr> {
+        Ok(self.first_child_of(id)?.is_none())
+    }
+
+    /// Returns whether the node is the root of the tree.
+    pub fn is_root(&self, id: usize) -> Result<bool, TreeErr> {
+        self.archived_node(id)?;
+
+        Ok(self.get_root() == Some(id))
+    }
+
+    /// Returns whether the first node is an ancestor of the second node, following the parents of the second node.
+    pub fn is_ancestor_of(&self, ancestor: usize, id: usize) -> Result<bool, TreeErr> {
+        self.archived_node(ancestor)?;
+        let mut parent = self.parent_of(id)?;
+
+        while let Some(parent_id) = parent {
+            if parent_id == ancestor { return Ok(true) }
+            parent = self.parent_of(parent_id)?;
+        }
+
+        Ok(false)
+    }
+
+    /// Returns whether the first node is a descendant of the second node.
+    pub fn is_descendant_of(&self, id: usize, ancestor: usize) -> Result<bool, TreeErr> {
+        self.is_ancestor_of(ancestor, id)
+    }
+
+    /// Returns the ids of the node followed by each of its ancestors up to and including the root.
+    pub fn path_to_root(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
+        let mut path = vec![id];
+        let mut parent = self.parent_of(id)?;
+
+        while let Some(parent_id) = parent {
+            path.push(parent_id);
+            parent = self.parent_of(parent_id)?;
+        }
+
+        Ok(path)
+    }
+
+    /// Returns the ids of the node and all of its descendants, in the same order as Tree::sub_tree.
+    pub fn sub_tree(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
+        let mut list = Vec::new();
+        let mut stack = vec![id];
+        self.archived_node(id)?;
+
+        while let Some(current) = stack.pop() {
+            list.push(current);
+
+            let mut child = self.last_child_of(current)?;
+            while let Some(child_id) = child {
+                stack.push(child_id);
+                child = self.prev_sib_of(child_id)?;
+            }
+        }
+
+        Ok(list)
+    }
+
+    /// Returns the id of the node after the given id going through the whole tree, in the same order as Tree::next_node.
+    pub fn next_node(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        if let Some(child) = self.first_child_of(id)? {
+            return Ok(Some(child));
+        }
+
+        let mut node = id;
+
+        loop {
+            if let Some(next) = self.next_sib_of(node)? {
+                return Ok(Some(next));
+            }
+
+            match self.parent_of(node)? {
+                Some(parent) => node = parent,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Returns the id of the node before the given id going through the whole tree, in the same order as Tree::prev_node.
+    pub fn prev_node(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        if let Some(mut node) = self.prev_sib_of(id)? {
+            while let Some(last) = self.last_child_of(node)? {
+                node = last;
+            }
+
+            return Ok(Some(node));
+        }
+
+        self.parent_of(id)
+    }
+}
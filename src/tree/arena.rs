@@ -0,0 +1,97 @@
+//! Conversions between trees and indextree arenas, enabled by the indextree feature.
+
+use super::*;
+use ::indextree::{Arena, NodeId};
+use std::convert::TryFrom;
+
+impl<T> Tree<T> {
+    //moves every node into the arena, returning the arena id of the root.
+    fn append_to_arena(mut self, arena: &mut Arena<T>) -> Option<NodeId> {
+        let root = self.root?;
+        let mut ids: HashMap<usize, NodeId> = HashMap::with_capacity(self.len);
+        let mut current = Some(root);
+
+        while let Some(id) = current {
+            let new_id = arena.new_node(self.take_data(id).unwrap());
+            if id != root {
+                ids[&self.nodes[id].parent.get().unwrap()].append(new_id, arena);
+            }
+            ids.insert(id, new_id);
+
+            current = self.next_in_sub_tree(id, root);
+        }
+
+        Some(ids[&root])
+    }
+
+    //takes each tree out of the arena in the order of its roots.
+    fn trees_from_arena(arena: Arena<T>) -> Vec<Tree<T>> {
+        let mut links = Vec::with_capacity(arena.len());
+        for root in arena.roots() {
+            for node in root.descendants(&arena) {
+                links.push((usize::from(node) - 1, arena[node].parent().map(|parent| usize::from(parent) - 1)));
+            }
+        }
+
+        let mut values: Vec<Option<T>> = arena.into_iter().map(|node| node.into_data()).collect();
+        let mut ids = vec![0; values.len()];
+        let mut trees: Vec<Tree<T>> = Vec::new();
+
+        for (index, parent) in links {
+            let data = values[index].take().unwrap();
+            ids[index] = match parent {
+                Some(parent) => {
+                    let tree = trees.last_mut().unwrap();
+                    let id = tree.get_node(data);
+                    tree.append_child(ids[parent], id);
+                    id
+                }
+                None => {
+                    trees.push(Tree::new_with_root(data));
+                    0
+                }
+            };
+        }
+
+        trees
+    }
+}
+
+impl<T> TryFrom<Arena<T>> for Tree<T> {
+    type Error = TreeErr;
+
+    /// Fails if the arena holds more than one tree, use a Forest for those.
+    fn try_from(arena: Arena<T>) -> Result<Self, TreeErr> {
+        let mut trees = Tree::trees_from_arena(arena);
+
+        match trees.len() {
+            0 => Ok(Tree::new()),
+            1 => Ok(trees.pop().unwrap()),
+            _ => Err(MultipleRoots),
+        }
+    }
+}
+
+impl<T> From<Arena<T>> for Forest<T> {
+    fn from(arena: Arena<T>) -> Self {
+        Forest::from_trees(Tree::trees_from_arena(arena))
+    }
+}
+
+impl<T> From<Tree<T>> for Arena<T> {
+    fn from(tree: Tree<T>) -> Self {
+        let mut arena = Arena::with_capacity(tree.len);
+        tree.append_to_arena(&mut arena);
+        arena
+    }
+}
+
+impl<T> From<Forest<T>> for Arena<T> {
+    fn from(mut forest: Forest<T>) -> Self {
+        let mut arena = Arena::with_capacity(forest.len());
+        for root in forest.roots() {
+            forest.remove_tree(root).unwrap().append_to_arena(&mut arena);
+        }
+        arena
+    }
+}
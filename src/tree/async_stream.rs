@@ -0,0 +1,63 @@
+//! Reading and writing the byte format with tokio's AsyncRead and AsyncWrite, enabled by the async feature.
+
+use super::*;
+use std::io::{self, BufReader, Read};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::runtime::Handle;
+
+//the number of bytes collected before each write.
+const BUFFER_SIZE: usize = 8192;
+
+//lets the blocking decoder read from an async reader by waiting on each read.
+struct BlockingReader<R> {
+    read: R,
+    handle: Handle,
+}
+
+impl<R: AsyncRead + Unpin> Read for BlockingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = &mut self.read;
+        self.handle.block_on(read.read(buf))
+    }
+}
+
+impl<'a, A: IntoBytes<'a>> Tree<A> {
+    /// Writes the same bytes as into_bytes to the async writer, a buffer at a time.
+    /// Only ids are kept between writes, so the future can be sent between threads if the data can be shared between them.
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&'a self, mut write: W) -> io::Result<()> {
+        let mut buffer: Vec<u8> = varint_bytes(self.len).collect();
+
+        if let Some(root) = self.root {
+            let mut current = Some(root);
+
+            while let Some(id) = current {
+                buffer.extend(self.data_of(id).unwrap().into_bytes());
+                buffer.extend(varint_bytes(self.count_children(id)));
+
+                if buffer.len() >= BUFFER_SIZE {
+                    write.write_all(&buffer).await?;
+                    buffer.clear();
+                }
+
+                current = self.next_in_sub_tree(id, root);
+            }
+        }
+
+        write.write_all(&buffer).await?;
+        write.flush().await
+    }
+}
+
+impl<A: FromBytes + Send + 'static> Tree<A> {
+    /// Reads a tree written by write_to_async or into_bytes from the async reader.
+    /// The bytes are decoded on one of tokio's blocking threads, which waits for each buffer of bytes as it arrives.
+    pub async fn read_from_async<R: AsyncRead + Unpin + Send + 'static>(read: R) -> Result<Tree<A>, ByteErr> {
+        let handle = Handle::current();
+
+        match tokio::task::spawn_blocking(move || Tree::read_from(BufReader::new(BlockingReader { read, handle }))).await {
+            Ok(result) => result,
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(err) => Err(ByteErr::Io(io::Error::other(err))),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! TreeBuilder builds a tree from a sequence of begin and end events, the way parsers and serializers produce them.
+
+use super::*;
+
+/// Builds a tree by opening and closing nodes, each node added is a child of the last node that is still open.
+/// 
+/// ## Functions
+/// - new
+/// 
+/// ## Methods
+/// - begin_node
+/// - end_node
+/// - leaf
+/// - depth
+/// - build
+pub struct TreeBuilder<T> {
+    tree: Tree<T>,
+    open: Vec<usize>,
+}
+
+impl<T> TreeBuilder<T> {
+    /// Creates a builder for an empty tree.
+    pub fn new() -> TreeBuilder<T> {
+        TreeBuilder {
+            tree: Tree::new(),
+            open: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, data: T) -> Result<usize, TreeErr> {
+        match self.open.last() {
+            Some(&parent) => self.tree.new_node(data, LastChild, parent),
+            None if self.tree.get_root().is_none() => Ok(self.tree.new_root(data)),
+            None => Err(MultipleRoots),
+        }
+    }
+
+    /// Adds a node containing the data provided and opens it, so the nodes added after it become its children until it's ended.
+    pub fn begin_node(&mut self, data: T) -> Result<usize, TreeErr> {
+        let id = self.add(data)?;
+        self.open.push(id);
+        Ok(id)
+    }
+
+    /// Closes the last node that was opened, returning its id.
+    pub fn end_node(&mut self) -> Result<usize, TreeErr> {
+        self.open.pop().ok_or(NoOpenNode)
+    }
+
+    /// Adds a node containing the data provided without opening it.
+    pub fn leaf(&mut self, data: T) -> Result<usize, TreeErr> {
+        self.add(data)
+    }
+
+    /// Returns the number of nodes that are currently open.
+    pub fn depth(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Returns the finished tree, any nodes that are still open are closed.
+    pub fn build(self) -> Tree<T> {
+        self.tree
+    }
+}
+
+impl<T> Default for TreeBuilder<T> {
+    fn default() -> Self {
+        TreeBuilder::new()
+    }
+}
@@ -0,0 +1,365 @@
+//! The byte format of the tree, used by IntoBytes and FromBytes.
+//!
+//! The tree starts with its node count, then each node in the same order as sub_tree as its data followed by its child count.
+//! The counts are LEB128 varints so small trees don't pay for a full usize per node.
+
+use super::*;
+
+//the bytes of a varint, kept inline so writing one doesn't allocate. Ten bytes fit all 64 bits.
+pub(super) struct VarintBytes {
+    bytes: [u8; 10],
+    len: usize,
+    pos: usize,
+}
+
+impl Iterator for VarintBytes {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.pos == self.len { return None }
+
+        self.pos += 1;
+        Some(self.bytes[self.pos - 1])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len - self.pos, Some(self.len - self.pos))
+    }
+}
+
+//writes the number as a LEB128 varint, seven bits to a byte with the high bit set on every byte but the last.
+pub(super) fn varint_bytes(mut value: usize) -> VarintBytes {
+    let mut varint = VarintBytes {
+        bytes: [0; 10],
+        len: 0,
+        pos: 0,
+    };
+
+    while value >= 0x80 {
+        varint.bytes[varint.len] = (value as u8 & 0x7f) | 0x80;
+        varint.len += 1;
+        value >>= 7;
+    }
+    varint.bytes[varint.len] = value as u8;
+    varint.len += 1;
+
+    varint
+}
+
+//the most nodes made room for up front when the number of bytes left isn't known, since the header could be lying.
+pub(super) const MAX_HEADER_CAPACITY: usize = 1 << 16;
+
+//the number of bytes varint_bytes gives for the value.
+pub(super) fn varint_len(value: usize) -> usize {
+    let bits = (usize::BITS - value.leading_zeros()) as usize;
+    std::cmp::max(1, bits.div_ceil(7))
+}
+
+//adds the seven bits of the byte at the shift, or gives usize::MAX if they don't fit.
+fn add_varint_bits(value: usize, byte: u8, shift: u32) -> usize {
+    let bits = (byte & 0x7f) as usize;
+    if bits == 0 { return value }
+
+    match bits.checked_shl(shift) {
+        Some(shifted) if shifted >> shift == bits => value | shifted,
+        _ => usize::MAX,
+    }
+}
+
+//reads a LEB128 varint. Counts too big for a usize read as usize::MAX, so reading runs out of bytes instead of quietly reading the wrong shape.
+pub(super) fn varint_from_bytes<T: Iterator<Item = u8>>(bytes: &mut T) -> Result<usize, ByteErr> {
+    let mut value = 0;
+    let mut shift = 0;
+
+    loop {
+        let byte = u8::from_bytes(bytes)?;
+        value = add_varint_bits(value, byte, shift);
+        if byte & 0x80 == 0 { return Ok(value) }
+        shift = shift.saturating_add(7);
+    }
+}
+
+pub(super) fn varint_from_io_bytes<T: Iterator<Item = Result<u8, std::io::Error>>>(bytes: &mut T) -> Result<usize, ByteErr> {
+    let mut value = 0;
+    let mut shift = 0;
+
+    loop {
+        let byte = u8::from_io_bytes(bytes)?;
+        value = add_varint_bits(value, byte, shift);
+        if byte & 0x80 == 0 { return Ok(value) }
+        shift = shift.saturating_add(7);
+    }
+}
+
+//the error for bytes that end before the tree they hold does, used for counts too big for any run of bytes to hold and headers that don't match the tree after them.
+pub(super) fn out_of_bytes() -> ByteErr {
+    ByteErr::EndOfBytes
+}
+
+//reads a value from the slice starting at the offset, returning it with the offset after it.
+pub(super) fn read_at_offset<'b, V, F: FnOnce(&mut std::iter::Copied<std::slice::Iter<'b, u8>>) -> Result<V, ByteErr>>(bytes: &'b [u8], offset: usize, read: F) -> Result<(V, usize), ByteErr> {
+    let mut iter = bytes[offset.min(bytes.len())..].iter().copied();
+    let value = read(&mut iter)?;
+    Ok((value, bytes.len() - iter.len()))
+}
+
+/// The u8 iterator for all of the data in the tree.
+pub struct TreeIter<'a, T>{
+    tree: &'a Tree<T>,
+    //the next node to turn into bytes, and the node whose sub tree is being turned into bytes.
+    next: Option<usize>,
+    top: usize,
+    //the bytes of the current node's data, then its child count. The box from into_bytes is the only allocation made per node.
+    data_iter: Option<Box<dyn std::iter::Iterator<Item = u8> + 'a>>,
+    count_iter: VarintBytes,
+}
+
+impl<'a, T> TreeIter<'a, T> {
+    fn new(tree: &'a Tree<T>) -> Self {
+        //the header is the number of nodes, so readers know how much room to make.
+        TreeIter::with_start(tree, varint_bytes(tree.len), tree.root, tree.root.unwrap_or(0))
+    }
+
+    //iterates over the nodes from first on in the sub tree of top, in the same order as sub_tree.
+    pub(super) fn with_start(tree: &'a Tree<T>, header: VarintBytes, first: Option<usize>, top: usize) -> Self {
+        TreeIter {
+            data_iter: None,
+            count_iter: header,
+            next: first,
+            top,
+            tree: tree,
+        }
+    }
+}
+
+impl<'a, T: IntoBytes<'a>> Iterator for TreeIter<'a, T> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item>{
+        loop {
+            if let Some(data_iter) = &mut self.data_iter {
+                if let Some(value) = data_iter.next() { return Some(value) }
+                self.data_iter = None;
+            }
+
+            if let Some(value) = self.count_iter.next() { return Some(value) }
+
+            let id = self.next?;
+            self.data_iter = Some(self.tree.data_of(id).unwrap().into_bytes());
+            self.count_iter = varint_bytes(self.tree.count_children(id));
+            self.next = self.tree.next_in_sub_tree(id, self.top);
+        }
+    }
+}
+
+impl<T> Tree<T> {
+    /// Returns the number of bytes into_bytes gives for the tree, given the number of bytes the data of each node takes.
+    pub fn byte_len<F: FnMut(&T) -> usize>(&self, mut data_len: F) -> usize {
+        let mut len = varint_len(self.len);
+        let mut node = self.root;
+
+        while let Some(id) = node {
+            len += data_len(self.data_of(id).unwrap()) + varint_len(self.count_children(id));
+            node = self.next_in_sub_tree(id, self.root.unwrap());
+        }
+
+        len
+    }
+}
+
+impl<'a, A: IntoBytes<'a>> IntoBytes<'a> for Tree<A>{
+
+    fn into_bytes(&'a self) -> Box<dyn Iterator<Item = u8> + 'a> {
+        Box::new(TreeIter::new(self))
+    }
+}
+
+impl<'a, A: IntoBytes<'a>> Tree<A> {
+    /// Returns the bytes of the sub tree of the given id in the same format as into_bytes, so from_bytes reads them back as a tree with the given node as its root.
+    pub fn subtree_into_bytes(&'a self, id: usize) -> Result<Box<dyn Iterator<Item = u8> + 'a>, TreeErr> {
+        let header = varint_bytes(self.subtree_size(id)?);
+
+        Ok(Box::new(TreeIter::with_start(self, header, Some(id), id)))
+    }
+}
+
+impl<A: FromBytes> Tree<A>{
+    //reads the sub trees under the parent, keeping the parents that still have children left to read on a stack so deep trees don't use up the call stack.
+    pub(super) fn read_sub_trees<I, E, D, C>(&mut self, parent: usize, bytes: &mut I, mut read_data: D, mut read_count: C) -> Result<(), E>
+    where
+        E: From<ByteErr>,
+        D: FnMut(&mut I) -> Result<A, E>,
+        C: FnMut(&mut I) -> Result<usize, ByteErr>,
+    {
+        let mut parents = vec![(parent, read_count(bytes)?)];
+
+        while let Some((parent, remaining)) = parents.last_mut() {
+            if *remaining == 0 {
+                parents.pop();
+                continue;
+            }
+            *remaining -= 1;
+            let parent = *parent;
+
+            let child = self.get_node(read_data(bytes)?);
+            self.append_child(parent, child);
+            parents.push((child, read_count(bytes)?));
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn from_bytes_helper<T: Iterator<Item = u8>>(&mut self, parent: usize, bytes: &mut T) -> Result<(), ByteErr>{
+        self.read_sub_trees(parent, bytes, A::from_bytes, varint_from_bytes)
+    }
+
+    pub(super) fn from_io_bytes_helper<T: Iterator<Item = Result<u8, std::io::Error>>>(&mut self, parent: usize, bytes: &mut T) -> Result<(), ByteErr>{
+        self.read_sub_trees(parent, bytes, A::from_io_bytes, varint_from_io_bytes)
+    }
+
+    fn read_depth_children<T: Iterator<Item = u8>>(&mut self, parent: usize, bytes: &mut T, max_depth: usize) -> Result<(), ByteErr>{
+        let child_count = varint_from_bytes(bytes)?;
+        if max_depth == 0 {
+            return Self::skip_from_bytes(bytes, child_count);
+        }
+
+        //the same as read_sub_trees, but the children of nodes at max_depth are skipped instead of being put on the stack.
+        let mut parents = vec![(parent, child_count)];
+
+        while let Some((parent, remaining)) = parents.last_mut() {
+            if *remaining == 0 {
+                parents.pop();
+                continue;
+            }
+            *remaining -= 1;
+            let parent = *parent;
+
+            let child = self.get_node(A::from_bytes(bytes)?);
+            self.append_child(parent, child);
+
+            let child_count = varint_from_bytes(bytes)?;
+            if parents.len() < max_depth {
+                parents.push((child, child_count));
+            }
+            else {
+                Self::skip_from_bytes(bytes, child_count)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    //reads past the given number of sub trees, still decoding their data since its length isn't known.
+    pub(super) fn skip_from_bytes<T: Iterator<Item = u8>>(bytes: &mut T, count: usize) -> Result<(), ByteErr>{
+        let mut remaining = count;
+
+        while remaining > 0 {
+            A::from_bytes(bytes)?;
+            remaining = (remaining - 1).checked_add(varint_from_bytes(bytes)?).ok_or_else(out_of_bytes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a tree from bytes like from_bytes, but only keeps the nodes up to max_depth below the root.
+    /// The deeper nodes are still read so the bytes after the tree are left at the right place.
+    pub fn from_bytes_depth<T: Iterator<Item = u8>>(bytes: &mut T, max_depth: usize) -> Result<Self, ByteErr>{
+        let mut tree = Tree::new();
+
+        if varint_from_bytes(bytes)? > 0 {
+            let root = tree.new_root(A::from_bytes(bytes)?);
+            tree.read_depth_children(root, bytes, max_depth)?;
+        }
+
+        Ok(tree)
+    }
+
+    //makes an empty tree with room for the node count from the header. Every node takes at least a byte, so the room is never more than the bytes left could hold.
+    pub(super) fn with_header_capacity(node_count: usize, bytes_left: Option<usize>) -> Tree<A> {
+        let mut tree = Tree::new();
+        tree.reserve(node_count.min(bytes_left.unwrap_or(MAX_HEADER_CAPACITY)));
+        tree
+    }
+
+    //checks the tree read holds as many nodes as the header before it said, a tree that doesn't was cut short or run into other bytes.
+    pub(super) fn check_node_count(self, node_count: usize) -> Result<Tree<A>, ByteErr> {
+        if self.len != node_count { return Err(out_of_bytes()) }
+        Ok(self)
+    }
+}
+
+/// The ways attaching a tree read from bytes can fail.
+#[derive(Debug)]
+pub enum AttachErr {
+    /// The bytes couldn't be read as a tree.
+    Bytes(ByteErr),
+    /// The tree can't be attached there, or the bytes hold an empty tree and there is nothing to attach.
+    Tree(TreeErr),
+}
+
+impl From<ByteErr> for AttachErr {
+    fn from(err: ByteErr) -> Self {
+        AttachErr::Bytes(err)
+    }
+}
+
+impl From<TreeErr> for AttachErr {
+    fn from(err: TreeErr) -> Self {
+        AttachErr::Tree(err)
+    }
+}
+
+impl<A: FromBytes> Tree<A> {
+    //attaches the root and reads the rest of the tree under it, removing what was attached if the bytes run out part way.
+    fn attach_read<I>(&mut self, bytes: &mut I, in_position: Position, node: usize, read_data: fn(&mut I) -> Result<A, ByteErr>, read_rest: fn(&mut Tree<A>, usize, &mut I) -> Result<(), ByteErr>, read_count: fn(&mut I) -> Result<usize, ByteErr>) -> Result<usize, AttachErr> {
+        self.valid_attach(&in_position, node)?;
+        if read_count(bytes)? == 0 { return Err(AttachErr::Tree(NoRoot)) }
+
+        let new = self.get_node(read_data(bytes)?);
+        self.attach(new, in_position, node);
+
+        if let Err(err) = read_rest(self, new, bytes) {
+            self.remove(new).unwrap();
+            return Err(AttachErr::Bytes(err));
+        }
+
+        Ok(new)
+    }
+
+    /// Reads a tree from bytes and attaches it to the node provided in the position of in_position, returning the id of its root.
+    /// The nodes are attached as they are read, so no separate tree is made first. If the bytes run out nothing is attached.
+    pub fn attach_from_bytes<T: Iterator<Item = u8>>(&mut self, bytes: &mut T, in_position: Position, node: usize) -> Result<usize, AttachErr> {
+        self.attach_read(bytes, in_position, node, A::from_bytes, Tree::from_bytes_helper, varint_from_bytes)
+    }
+
+    /// Reads a tree from io bytes and attaches it to the node provided in the position of in_position, returning the id of its root.
+    pub fn attach_from_io_bytes<T: Iterator<Item = Result<u8, std::io::Error>>>(&mut self, bytes: &mut T, in_position: Position, node: usize) -> Result<usize, AttachErr> {
+        self.attach_read(bytes, in_position, node, A::from_io_bytes, Tree::from_io_bytes_helper, varint_from_io_bytes)
+    }
+}
+
+impl<A: FromBytes> FromBytes for Tree<A>{
+    fn from_bytes<T: Iterator<Item = u8>>(bytes: &mut T) -> Result<Self, ByteErr>{
+        let node_count = varint_from_bytes(bytes)?;
+        let mut tree = Tree::with_header_capacity(node_count, bytes.size_hint().1);
+
+        if node_count > 0 {
+            let root = tree.new_root(A::from_bytes(bytes)?);
+            tree.from_bytes_helper(root, bytes)?;
+        }
+
+        tree.check_node_count(node_count)
+    }
+
+    fn from_io_bytes<T: Iterator<Item = Result<u8, std::io::Error>>>(bytes: &mut T) -> Result<Self, ByteErr>{
+        let node_count = varint_from_io_bytes(bytes)?;
+        let mut tree = Tree::with_header_capacity(node_count, bytes.size_hint().1);
+
+        if node_count > 0 {
+            let root = tree.new_root(A::from_io_bytes(bytes)?);
+            tree.from_io_bytes_helper(root, bytes)?;
+        }
+
+        tree.check_node_count(node_count)
+    }
+}
@@ -0,0 +1,155 @@
+//! The checked byte format, which is the normal byte format of the tree followed by a CRC-32 of those bytes.
+
+use super::*;
+
+/// The ways reading checked bytes can fail.
+#[derive(Debug)]
+pub enum ChecksumErr {
+    /// The bytes ran out before the checksum, or reading them from io failed.
+    Bytes(ByteErr),
+    /// The bytes couldn't be read as a tree or don't match their checksum, so they were changed after being written.
+    Corrupted,
+}
+
+impl From<ByteErr> for ChecksumErr {
+    fn from(err: ByteErr) -> Self {
+        ChecksumErr::Bytes(err)
+    }
+}
+
+//the CRC-32 used by zip and png, with the reversed 0xedb88320 polynomial.
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+static CRC_TABLE: [u32; 256] = crc_table();
+
+fn crc_update(crc: u32, byte: u8) -> u32 {
+    CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+}
+
+//reads the little endian checksum after the tree.
+fn read_footer<E, F: FnMut() -> Result<u8, E>>(mut read_byte: F) -> Result<u32, E> {
+    let mut footer = [0; 4];
+    for byte in &mut footer {
+        *byte = read_byte()?;
+    }
+    Ok(u32::from_le_bytes(footer))
+}
+
+/// The u8 iterator for checked bytes, which passes the bytes through and adds their little endian checksum to the end.
+pub struct ChecksumIter<'a> {
+    bytes: Box<dyn Iterator<Item = u8> + 'a>,
+    crc: u32,
+    footer: Option<std::array::IntoIter<u8, 4>>,
+}
+
+impl<'a> Iterator for ChecksumIter<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if let Some(footer) = &mut self.footer {
+            return footer.next()
+        }
+
+        match self.bytes.next() {
+            Some(byte) => {
+                self.crc = crc_update(self.crc, byte);
+                Some(byte)
+            }
+            None => {
+                let mut footer = IntoIterator::into_iter((!self.crc).to_le_bytes());
+                let first = footer.next();
+                self.footer = Some(footer);
+                first
+            }
+        }
+    }
+}
+
+//passes bytes through while adding them to the checksum.
+struct ChecksumReader<'a, T> {
+    bytes: &'a mut T,
+    crc: u32,
+}
+
+impl<'a, T: Iterator<Item = u8>> Iterator for ChecksumReader<'a, T> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.bytes.next()?;
+        self.crc = crc_update(self.crc, byte);
+        Some(byte)
+    }
+}
+
+//the same as ChecksumReader, for bytes from io, remembering whether the io failed.
+struct ChecksumIoReader<'a, T> {
+    bytes: &'a mut T,
+    crc: u32,
+    failed: bool,
+}
+
+impl<'a, T: Iterator<Item = Result<u8, std::io::Error>>> Iterator for ChecksumIoReader<'a, T> {
+    type Item = Result<u8, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let byte = self.bytes.next()?;
+        match byte {
+            Ok(byte) => self.crc = crc_update(self.crc, byte),
+            Err(_) => self.failed = true,
+        }
+        Some(byte)
+    }
+}
+
+impl<'a, A: IntoBytes<'a>> Tree<A> {
+    /// Returns the same bytes as into_bytes followed by a CRC-32 of them, so corruption can be found when reading them back with from_checked_bytes.
+    pub fn into_checked_bytes(&'a self) -> ChecksumIter<'a> {
+        ChecksumIter {
+            bytes: self.into_bytes(),
+            crc: !0,
+            footer: None,
+        }
+    }
+}
+
+impl<A: FromBytes> Tree<A> {
+    /// Reads a tree written by into_checked_bytes, failing with Corrupted if the bytes can't be read as a tree or don't match the checksum after them.
+    pub fn from_checked_bytes<T: Iterator<Item = u8>>(bytes: &mut T) -> Result<Tree<A>, ChecksumErr> {
+        let mut reader = ChecksumReader { bytes, crc: !0 };
+        let tree = Tree::from_bytes(&mut reader).map_err(|_| ChecksumErr::Corrupted)?;
+        let crc = !reader.crc;
+
+        if read_footer(|| u8::from_bytes(reader.bytes))? != crc { return Err(ChecksumErr::Corrupted) }
+        Ok(tree)
+    }
+
+    /// The same as from_checked_bytes, for bytes from io. A failed read is still returned as the io error rather than Corrupted.
+    pub fn from_checked_io_bytes<T: Iterator<Item = Result<u8, std::io::Error>>>(bytes: &mut T) -> Result<Tree<A>, ChecksumErr> {
+        let mut reader = ChecksumIoReader { bytes, crc: !0, failed: false };
+        let tree = match Tree::from_io_bytes(&mut reader) {
+            Ok(tree) => tree,
+            Err(err) if reader.failed => return Err(ChecksumErr::Bytes(err)),
+            Err(_) => return Err(ChecksumErr::Corrupted),
+        };
+        let crc = !reader.crc;
+
+        if read_footer(|| u8::from_io_bytes(reader.bytes))? != crc { return Err(ChecksumErr::Corrupted) }
+        Ok(tree)
+    }
+}
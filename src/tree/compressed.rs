@@ -0,0 +1,131 @@
+//! Deflate compressed bytes, enabled by the compression feature. Both directions stream, so the uncompressed bytes are never held all at once.
+
+use super::*;
+use flate2::{Compression, Decompress, FlushDecompress, Status};
+use flate2::write::DeflateEncoder;
+use std::io::{self, Write};
+
+//the number of uncompressed bytes handed to the encoder, or taken from the decoder, at a time.
+const CHUNK_SIZE: usize = 4096;
+
+/// The u8 iterator for compressed bytes, which compresses the bytes of the tree a chunk at a time as they are needed.
+pub struct CompressedIter<'a> {
+    bytes: Box<dyn Iterator<Item = u8> + 'a>,
+    encoder: Option<DeflateEncoder<Vec<u8>>>,
+    out: std::vec::IntoIter<u8>,
+}
+
+impl<'a> Iterator for CompressedIter<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        loop {
+            if let Some(byte) = self.out.next() {
+                return Some(byte)
+            }
+
+            let encoder = self.encoder.as_mut()?;
+            let chunk: Vec<u8> = self.bytes.by_ref().take(CHUNK_SIZE).collect();
+
+            //writing to a vec can't fail.
+            self.out = if chunk.is_empty() {
+                self.encoder.take().unwrap().finish().unwrap().into_iter()
+            }
+            else {
+                encoder.write_all(&chunk).unwrap();
+                std::mem::take(encoder.get_mut()).into_iter()
+            };
+        }
+    }
+}
+
+//inflates compressed bytes from an iterator, taking them one at a time so none past the end of the compressed bytes are taken.
+struct Inflater<'a, T> {
+    bytes: &'a mut T,
+    inflate: Decompress,
+    //a byte taken from the iterator that the inflater hasn't used yet.
+    pending: Option<u8>,
+    done: bool,
+    //the inflated bytes not handed out yet, from next on.
+    out: Vec<u8>,
+    next: usize,
+}
+
+impl<'a, T: Iterator<Item = u8>> Inflater<'a, T> {
+    //inflates the next run of bytes into out, leaving it empty once the compressed bytes end.
+    fn fill(&mut self) -> io::Result<()> {
+        self.out.clear();
+        self.next = 0;
+
+        while !self.done {
+            let input = match &self.pending {
+                Some(byte) => std::slice::from_ref(byte),
+                None => &[],
+            };
+            let total_in = self.inflate.total_in();
+            let status = self.inflate.decompress_vec(input, &mut self.out, FlushDecompress::None)?;
+
+            if self.inflate.total_in() != total_in { self.pending = None }
+            if status == Status::StreamEnd { self.done = true }
+            if self.done || !self.out.is_empty() { break }
+
+            if self.pending.is_some() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "the compressed bytes can't be inflated"))
+            }
+            match self.bytes.next() {
+                Some(byte) => self.pending = Some(byte),
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, T: Iterator<Item = u8>> Iterator for Inflater<'a, T> {
+    type Item = io::Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.out.len() {
+            if let Err(err) = self.fill() { return Some(Err(err)) }
+        }
+
+        let byte = *self.out.get(self.next)?;
+        self.next += 1;
+        Some(Ok(byte))
+    }
+}
+
+impl<'a, A: IntoBytes<'a>> Tree<A> {
+    /// Returns the bytes of into_bytes compressed with deflate.
+    pub fn into_bytes_compressed(&'a self) -> CompressedIter<'a> {
+        CompressedIter {
+            bytes: self.into_bytes(),
+            encoder: Some(DeflateEncoder::new(Vec::new(), Compression::default())),
+            out: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<A: FromBytes> Tree<A> {
+    /// Reads a tree from bytes written by into_bytes_compressed, taking no bytes past the end of them so more can be read after.
+    pub fn from_bytes_compressed<T: Iterator<Item = u8>>(bytes: &mut T) -> Result<Tree<A>, ByteErr> {
+        let mut inflater = Inflater {
+            bytes,
+            inflate: Decompress::new(false),
+            pending: None,
+            done: false,
+            out: Vec::with_capacity(CHUNK_SIZE),
+            next: 0,
+        };
+        let tree = Tree::from_io_bytes(&mut inflater)?;
+
+        //the end of the compressed bytes can come after the last byte of the tree, so it is read past too.
+        let mut rest = inflater.peekable();
+        while rest.peek().is_some() {
+            u8::from_io_bytes(&mut rest)?;
+        }
+
+        Ok(tree)
+    }
+}
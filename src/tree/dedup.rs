@@ -0,0 +1,90 @@
+//! The deduplicated byte format, where each different piece of data is written once in a table and the nodes refer to it by index.
+//! Trees that repeat a small set of data across many nodes only pay for a varint per node instead of a full copy.
+
+use super::*;
+
+/// The ways reading deduplicated bytes can fail.
+#[derive(Debug)]
+pub enum DedupErr {
+    /// The bytes couldn't be read as a tree.
+    Bytes(ByteErr),
+    /// A node refers to data past the end of the table.
+    UnknownData,
+}
+
+impl From<ByteErr> for DedupErr {
+    fn from(err: ByteErr) -> Self {
+        DedupErr::Bytes(err)
+    }
+}
+
+impl<'a, A: IntoBytes<'a>> Tree<A> {
+    /// Returns the bytes of the tree in the deduplicated format, where data with the same bytes is only written once.
+    /// Starts with the node count, then the table of data, then each node in the same order as into_bytes as the index of its data and its child count.
+    pub fn into_dedup_bytes(&'a self) -> Vec<u8> {
+        let mut table: HashMap<Vec<u8>, usize> = HashMap::new();
+        let mut entries = Vec::new();
+        let mut nodes = Vec::new();
+
+        if let Some(root) = self.root {
+            let mut current = Some(root);
+
+            while let Some(id) = current {
+                let data: Vec<u8> = self.data_of(id).unwrap().into_bytes().collect();
+                let index = match table.entry(data) {
+                    Entry::Occupied(entry) => *entry.get(),
+                    Entry::Vacant(entry) => {
+                        entries.push(entry.key().clone());
+                        *entry.insert(entries.len() - 1)
+                    }
+                };
+
+                nodes.extend(varint_bytes(index));
+                nodes.extend(varint_bytes(self.count_children(id)));
+                current = self.next_in_sub_tree(id, root);
+            }
+        }
+
+        let mut bytes: Vec<u8> = varint_bytes(self.len).chain(varint_bytes(entries.len())).collect();
+        for entry in entries {
+            bytes.extend(entry);
+        }
+        bytes.extend(nodes);
+
+        bytes
+    }
+}
+
+impl<A: FromBytes + Clone> Tree<A> {
+    fn read_dedup<I: Iterator>(bytes: &mut I, read_data: fn(&mut I) -> Result<A, ByteErr>, read_varint: fn(&mut I) -> Result<usize, ByteErr>) -> Result<Tree<A>, DedupErr> {
+        let node_count = read_varint(bytes)?;
+        let mut tree = Tree::with_header_capacity(node_count, bytes.size_hint().1);
+
+        //every entry takes at least a byte, so the table is never given more room than the bytes left could fill.
+        let entry_count = read_varint(bytes)?;
+        let mut table = Vec::with_capacity(entry_count.min(bytes.size_hint().1.unwrap_or(MAX_HEADER_CAPACITY)));
+        for _ in 0..entry_count {
+            table.push(read_data(bytes)?);
+        }
+
+        if node_count > 0 {
+            let data = table.get(read_varint(bytes)?).ok_or(DedupErr::UnknownData)?;
+            let root = tree.new_root(data.clone());
+            tree.read_sub_trees(root, bytes, |bytes| {
+                table.get(read_varint(bytes)?).cloned().ok_or(DedupErr::UnknownData)
+            }, read_varint)?;
+        }
+
+        Ok(tree.check_node_count(node_count)?)
+    }
+
+    /// Reads a tree written by into_dedup_bytes, cloning the data in the table into each node that uses it.
+    pub fn from_dedup_bytes<T: Iterator<Item = u8>>(bytes: &mut T) -> Result<Tree<A>, DedupErr> {
+        Tree::read_dedup(bytes, A::from_bytes, varint_from_bytes)
+    }
+
+    /// Reads a tree written by into_dedup_bytes from io bytes, cloning the data in the table into each node that uses it.
+    pub fn from_dedup_io_bytes<T: Iterator<Item = Result<u8, std::io::Error>>>(bytes: &mut T) -> Result<Tree<A>, DedupErr> {
+        Tree::read_dedup(bytes, A::from_io_bytes, varint_from_io_bytes)
+    }
+}
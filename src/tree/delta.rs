@@ -0,0 +1,263 @@
+//! Deltas between two versions of a tree, so only the changes have to be stored or sent instead of the whole tree.
+
+use super::*;
+
+/// The changes that turn one tree into another, made by delta_to and used by apply_delta.
+/// Nodes are matched by id, so a delta only makes sense between a tree and a changed version of that same tree.
+/// 
+/// ## Methods
+/// - is_empty
+/// ### if impl IntoBytes (bytebuffer feature)
+/// - into_bytes
+/// ### if impl FromBytes (bytebuffer feature)
+/// - from_bytes
+/// - from_io_bytes
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TreeDelta<T> {
+    root: Option<usize>,
+    removed: Vec<usize>,
+    //the data of the nodes that were added or changed.
+    data: Vec<(usize, T)>,
+    //the children of the nodes whose children changed, in order.
+    children: Vec<(usize, Vec<usize>)>,
+}
+
+impl<T> TreeDelta<T> {
+    /// Returns true if the delta doesn't change anything.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.data.is_empty() && self.children.is_empty()
+    }
+}
+
+impl<T> Tree<T> {
+    /// Changes the tree by the delta, which has to have been made from this tree by delta_to. Ids are kept, so nodes added by the delta have the same ids as in the changed tree.
+    /// The tree is left as it was if the delta doesn't fit it.
+    pub fn apply_delta(&mut self, delta: TreeDelta<T>) -> Result<(), TreeErr> {
+        let added = delta.data.iter().map(|&(id, _)| id).filter(|&id| self.valid_node(id).is_err());
+        let slot_count = Tree::<T>::listed_slot_count(added, self.len + delta.data.len())?.max(self.nodes.len());
+        let mut live: Vec<bool> = (0..slot_count).map(|id| self.valid_node(id).is_ok()).collect();
+
+        for &id in &delta.removed {
+            self.valid_node(id)?;
+            live[id] = false;
+        }
+        let removed: HashSet<usize> = delta.removed.iter().copied().collect();
+        for &(id, _) in &delta.data {
+            live[id] = true;
+        }
+
+        let changed_children: HashMap<usize, &Vec<usize>> = delta.children.iter().map(|(id, children)| (*id, children)).collect();
+
+        //walks the changed tree before touching anything, so a delta that doesn't fit is found before the tree is changed.
+        let mut listed = Vec::with_capacity(live.iter().filter(|&&live| live).count());
+        let mut visited = vec![false; slot_count];
+        let mut stack: Vec<(usize, Option<usize>)> = delta.root.into_iter().map(|root| (root, None)).collect();
+
+        while let Some((id, parent)) = stack.pop() {
+            if id >= slot_count || !live[id] { return Err(InvalidId) }
+            if visited[id] { return Err(MultipleParents) }
+            visited[id] = true;
+            listed.push((id, parent));
+
+            match changed_children.get(&id) {
+                Some(children) => stack.extend(children.iter().rev().map(|&child| (child, Some(id)))),
+                None if self.valid_node(id).is_ok() && !removed.contains(&id) => {
+                    let mut child = self.nodes[id].last_child.get();
+                    while let Some(child_id) = child {
+                        stack.push((child_id, Some(id)));
+                        child = self.nodes[child_id].prev_sib.get();
+                    }
+                }
+                None => {}
+            }
+        }
+
+        //every node left has to be reachable from the root.
+        if listed.len() != live.iter().filter(|&&live| live).count() { return Err(MultipleRoots) }
+
+        for &id in &delta.removed {
+            self.take_data(id);
+        }
+        self.nodes.resize(slot_count, Slot::Free(Link::NONE));
+        for (id, data) in delta.data {
+            match self.data_of_mut(id) {
+                Some(old) => *old = data,
+                None => self.put_data(id, data),
+            }
+        }
+
+        self.free = None;
+        for id in (0..slot_count).rev() {
+            if self.data_of(id).is_none() {
+                self.nodes[id] = Slot::Free(Link::new(self.free));
+                self.free = Some(id);
+            }
+        }
+
+        //the nodes are listed with each parent before its children and the children in order, so appending each one in turn gives the changed shape.
+        for &(id, parent) in &listed {
+            let node = &mut *self.nodes[id];
+            node.parent = Link::new(parent);
+            node.prev_sib = Link::NONE;
+            node.next_sib = Link::NONE;
+            node.first_child = Link::NONE;
+            node.last_child = Link::NONE;
+            node.child_count = 0;
+            #[cfg(feature = "subtree_sizes")]
+            { node.size = 1; }
+
+            match parent {
+                Some(parent) => {
+                    self.nodes[id].depth = self.nodes[parent].depth + 1;
+                    match self.nodes[parent].last_child.get() {
+                        Some(prev) => {
+                            self.nodes[prev].next_sib = Link::some(id);
+                            self.nodes[id].prev_sib = Link::some(prev);
+                        }
+                        None => self.nodes[parent].first_child = Link::some(id),
+                    }
+                    self.nodes[parent].last_child = Link::some(id);
+                    self.nodes[parent].child_count += 1;
+                }
+                None => self.nodes[id].depth = 0,
+            }
+        }
+
+        #[cfg(feature = "subtree_sizes")]
+        for &(id, parent) in listed.iter().rev() {
+            if let Some(parent) = parent {
+                self.nodes[parent].size += self.nodes[id].size;
+            }
+        }
+
+        self.root = delta.root;
+        self.len = listed.len();
+        Ok(())
+    }
+}
+
+impl<T: Clone + PartialEq> Tree<T> {
+    //returns true if the node has the same children in the same order in both trees, walking the links instead of listing them.
+    fn same_children(&self, other: &Tree<T>, id: usize) -> bool {
+        let mut child = self.nodes[id].first_child.get();
+        let mut other_child = other.nodes[id].first_child.get();
+
+        while child == other_child {
+            match child {
+                Some(child_id) => {
+                    child = self.nodes[child_id].next_sib.get();
+                    other_child = other.nodes[child_id].next_sib.get();
+                }
+                None => return true,
+            }
+        }
+
+        false
+    }
+
+    /// Returns the changes that turn this tree into the other tree, which should be a changed version of this tree so their ids match up.
+    pub fn delta_to(&self, other: &Tree<T>) -> TreeDelta<T> {
+        let removed = (0..self.nodes.len()).filter(|&id| self.valid_node(id).is_ok() && other.valid_node(id).is_err()).collect();
+        let mut data = Vec::new();
+        let mut children = Vec::new();
+
+        for id in (0..other.nodes.len()).filter(|&id| other.valid_node(id).is_ok()) {
+            let other_data = other.data_of(id).unwrap();
+            let (old_data, same_children) = if self.valid_node(id).is_ok() {
+                (self.data_of(id), self.same_children(other, id))
+            }
+            else { (None, other.count_children(id) == 0) };
+
+            if old_data != Some(other_data) {
+                data.push((id, other_data.clone()));
+            }
+
+            if !same_children {
+                children.push((id, other.children_of(id).unwrap()));
+            }
+        }
+
+        TreeDelta {
+            root: other.root,
+            removed,
+            data,
+            children,
+        }
+    }
+}
+
+#[cfg(feature = "bytebuffer")]
+impl<'a, A: IntoBytes<'a>> IntoBytes<'a> for TreeDelta<A> {
+    fn into_bytes(&'a self) -> Box<dyn Iterator<Item = u8> + 'a> {
+        //the root is stored one higher so no root can be zero.
+        let mut bytes: Vec<u8> = varint_bytes(self.root.map_or(0, |root| root + 1)).collect();
+
+        bytes.extend(varint_bytes(self.removed.len()));
+        for &id in &self.removed {
+            bytes.extend(varint_bytes(id));
+        }
+
+        bytes.extend(varint_bytes(self.data.len()));
+        for (id, data) in &self.data {
+            bytes.extend(varint_bytes(*id));
+            bytes.extend(data.into_bytes());
+        }
+
+        bytes.extend(varint_bytes(self.children.len()));
+        for (id, children) in &self.children {
+            bytes.extend(varint_bytes(*id));
+            bytes.extend(varint_bytes(children.len()));
+            for &child in children {
+                bytes.extend(varint_bytes(child));
+            }
+        }
+
+        Box::new(bytes.into_iter())
+    }
+}
+
+#[cfg(feature = "bytebuffer")]
+impl<A: FromBytes> TreeDelta<A> {
+    fn read<I>(bytes: &mut I, read_data: fn(&mut I) -> Result<A, ByteErr>, read_varint: fn(&mut I) -> Result<usize, ByteErr>) -> Result<Self, ByteErr> {
+        let root = read_varint(bytes)?.checked_sub(1);
+
+        let mut removed = Vec::new();
+        for _ in 0..read_varint(bytes)? {
+            removed.push(read_varint(bytes)?);
+        }
+
+        let mut data = Vec::new();
+        for _ in 0..read_varint(bytes)? {
+            data.push((read_varint(bytes)?, read_data(bytes)?));
+        }
+
+        let mut children = Vec::new();
+        for _ in 0..read_varint(bytes)? {
+            let id = read_varint(bytes)?;
+            let mut ids = Vec::new();
+            for _ in 0..read_varint(bytes)? {
+                ids.push(read_varint(bytes)?);
+            }
+            children.push((id, ids));
+        }
+
+        Ok(TreeDelta {
+            root,
+            removed,
+            data,
+            children,
+        })
+    }
+}
+
+#[cfg(feature = "bytebuffer")]
+impl<A: FromBytes> FromBytes for TreeDelta<A> {
+    fn from_bytes<T: Iterator<Item = u8>>(bytes: &mut T) -> Result<Self, ByteErr> {
+        TreeDelta::read(bytes, A::from_bytes, varint_from_bytes)
+    }
+
+    fn from_io_bytes<T: Iterator<Item = Result<u8, std::io::Error>>>(bytes: &mut T) -> Result<Self, ByteErr> {
+        TreeDelta::read(bytes, A::from_io_bytes, varint_from_io_bytes)
+    }
+}
@@ -0,0 +1,53 @@
+//! Conversions between trees and ego-tree trees, enabled by the ego-tree feature.
+
+use super::*;
+use std::convert::TryFrom;
+
+impl<T> From<::ego_tree::Tree<T>> for Tree<T> {
+    /// Nodes that are not connected to the ego-tree root are dropped.
+    fn from(other: ::ego_tree::Tree<T>) -> Self {
+        //ego-tree ids can't be turned into indices directly, but its nodes are listed in index order.
+        let indices: HashMap<::ego_tree::NodeId, usize> = other.nodes().enumerate().map(|(index, node)| (node.id(), index)).collect();
+        let links: Vec<(usize, Option<usize>)> = other.root().descendants()
+            .map(|node| (indices[&node.id()], node.parent().map(|parent| indices[&parent.id()])))
+            .collect();
+
+        let mut values: Vec<Option<T>> = other.into_iter().map(Some).collect();
+        let mut ids = vec![0; values.len()];
+        let mut tree = Tree::new();
+
+        for (index, parent) in links {
+            let id = tree.get_node(values[index].take().unwrap());
+            match parent {
+                Some(parent) => tree.append_child(ids[parent], id),
+                None => tree.root = Some(id),
+            }
+            ids[index] = id;
+        }
+
+        tree
+    }
+}
+
+impl<T> TryFrom<Tree<T>> for ::ego_tree::Tree<T> {
+    type Error = TreeErr;
+
+    /// Fails if the tree is empty, since an ego-tree always has a root.
+    fn try_from(mut tree: Tree<T>) -> Result<Self, TreeErr> {
+        let root = tree.root.ok_or(NoRoot)?;
+        let mut other = ::ego_tree::Tree::with_capacity(tree.take_data(root).unwrap(), tree.len);
+        let mut ids = HashMap::with_capacity(tree.len);
+        ids.insert(root, other.root().id());
+
+        let mut current = tree.next_in_sub_tree(root, root);
+        while let Some(id) = current {
+            let parent = ids[&tree.nodes[id].parent.get().unwrap()];
+            let new_id = other.get_mut(parent).unwrap().append(tree.take_data(id).unwrap()).id();
+            ids.insert(id, new_id);
+
+            current = tree.next_in_sub_tree(id, root);
+        }
+
+        Ok(other)
+    }
+}
@@ -0,0 +1,438 @@
+//! Forest is a collection of trees sharing the same storage, for data that has no single natural root.
+
+#[cfg(feature = "bytebuffer")]
+use bytebuffer::*;
+use super::*;
+
+/// The id of the hidden node that all of the roots of the forest are children of.
+const TOP: usize = 0;
+
+/// A collection of nodes and there relations with any number of roots.
+/// 
+/// ## Functions
+/// - new
+/// - from_trees
+/// 
+/// ## Methods
+/// - len
+/// - is_empty
+/// - contains
+/// - roots
+/// - add_root
+/// - add_tree
+/// - remove_tree
+/// - descendants_of
+/// - sub_tree
+/// - sub_tree_info
+/// - sub_tree_depth
+/// - sub_tree_depth_info
+/// - children_of
+/// - depth_of
+/// - height_of
+/// - is_ancestor_of
+/// - is_descendant_of
+/// - is_leaf
+/// - is_root
+/// - child_count
+/// - nth_child
+/// - sibling_index
+/// - path_to_root
+/// - path_between
+/// - next_node
+/// - prev_node
+/// - range
+/// - find
+/// - find_all
+/// - new_node
+/// - new_nodes
+/// - remove
+/// - remove_many
+/// - data_at
+/// - data_at_mut
+/// - data_at_many_mut
+/// - parent_of
+/// - next_sib_of
+/// - prev_sib_of
+/// - first_child_of
+/// - last_child_of
+/// - move_to
+/// ### if impl Clone
+/// - clone_to
+/// ### if impl IntoBytes (bytebuffer feature)
+/// - into_bytes
+/// ### if impl FromBytes (bytebuffer feature)
+/// - from_bytes
+/// - from_io_bytes
+/// ### if impl Serialize or Deserialize (serde feature)
+/// - serialize
+/// - deserialize
+pub struct Forest<T> {
+    tree: Tree<T>,
+}
+
+impl<T> Forest<T> {
+    /// Creates an empty forest.
+    pub fn new() -> Forest<T> {
+        Forest {
+            tree: Tree {
+                free: None,
+                nodes: vec![
+                    Slot::Hidden(Node {
+                        parent:      Link::NONE,
+                        prev_sib:    Link::NONE,
+                        next_sib:    Link::NONE,
+                        first_child: Link::NONE,
+                        last_child:  Link::NONE,
+                        #[cfg(feature = "subtree_sizes")]
+                        size:        0,
+                        child_count: 0,
+                        depth:       0,
+                    }),
+                ],
+                data: Vec::new(),
+                owners: Vec::new(),
+                root: Some(TOP),
+                len: 0,
+            },
+        }
+    }
+
+    /// Creates a forest with each of the provided trees as one of its roots.
+    pub fn from_trees<I: IntoIterator<Item = Tree<T>>>(trees: I) -> Forest<T> {
+        let mut forest = Forest::new();
+
+        for tree in trees {
+            forest.add_tree(tree);
+        }
+
+        forest
+    }
+
+    /// Returns the number of nodes currently in the forest.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns true if the forest has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether the given id is currently a node in the forest.
+    pub fn contains(&self, id: usize) -> bool {
+        self.tree.contains(id)
+    }
+
+    /// Returns the ids of all of the roots of the forest in order.
+    pub fn roots(&self) -> Vec<usize> {
+        let mut roots = Vec::new();
+        let mut root = self.tree.nodes[TOP].first_child.get();
+
+        while let Some(root_id) = root {
+            roots.push(root_id);
+            root = self.tree.nodes[root_id].next_sib.get();
+        }
+
+        roots
+    }
+
+    /// Creates a new node containing the data provided as the last root of the forest.
+    pub fn add_root(&mut self, data: T) -> usize {
+        let new = self.tree.get_node(data);
+        self.tree.append_child(TOP, new);
+        new
+    }
+
+    /// Moves all of the nodes of the tree into the forest as its last root, returning the id of the new root.
+    pub fn add_tree(&mut self, tree: Tree<T>) -> Option<usize> {
+        let new = self.tree.graft(tree)?;
+        self.tree.append_child(TOP, new);
+        Some(new)
+    }
+
+    /// Removes the given node along with all of its descendants from the forest, returning them as a tree of their own.
+    pub fn remove_tree(&mut self, id: usize) -> Result<Tree<T>, TreeErr> {
+        self.tree.valid_node(id)?;
+
+        Ok(self.tree.split_off(id))
+    }
+
+    /// Returns a list of all of the descendants of the provided id.
+    pub fn descendants_of(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
+        self.tree.descendants_of(id)
+    }
+
+    /// Returns a list starting with the id provided followed by all of its descendants.
+    pub fn sub_tree(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
+        self.tree.sub_tree(id)
+    }
+
+    /// Returns a list starting with the id provided and the number of children it has followed by the same for all of its descendants.
+    pub fn sub_tree_info(&self, id: usize) -> Result<Vec<NodeInfo>, TreeErr> {
+        self.tree.sub_tree_info(id)
+    }
+
+    /// Returns a list starting with the id provided followed by all of its descendants up to the given depth.
+    pub fn sub_tree_depth(&self, id: usize, depth: usize) -> Result<Vec<usize>, TreeErr> {
+        self.tree.sub_tree_depth(id, depth)
+    }
+
+    /// Returns a list starting with the id provided and the number of children it has followed by the same for all of its descendants up to the given depth.
+    pub fn sub_tree_depth_info(&self, id: usize, depth: usize) -> Result<Vec<NodeInfo>, TreeErr> {
+        self.tree.sub_tree_depth_info(id, depth)
+    }
+
+    /// Returns a list of all of the child ids of the given node.
+    pub fn children_of(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
+        self.tree.children_of(id)
+    }
+
+    /// Returns the number of ancestors the given node has, roots have a depth of zero.
+    pub fn depth_of(&self, id: usize) -> Result<usize, TreeErr> {
+        Ok(self.tree.depth_of(id)? - 1)
+    }
+
+    /// Returns the number of levels below the given node, following the longest path down to a leaf. A node without children has a height of zero.
+    pub fn height_of(&self, id: usize) -> Result<usize, TreeErr> {
+        self.tree.height_of(id)
+    }
+
+    /// Returns whether the first node is an ancestor of the second node, following the parents of the second node.
+    pub fn is_ancestor_of(&self, ancestor: usize, id: usize) -> Result<bool, TreeErr> {
+        self.tree.is_ancestor_of(ancestor, id)
+    }
+
+    /// Returns whether the first node is a descendant of the second node.
+    pub fn is_descendant_of(&self, id: usize, ancestor: usize) -> Result<bool, TreeErr> {
+        self.tree.is_descendant_of(id, ancestor)
+    }
+
+    /// Returns whether the given node has no children.
+    pub fn is_leaf(&self, id: usize) -> Result<bool, TreeErr> {
+        self.tree.is_leaf(id)
+    }
+
+    /// Returns whether the given node is one of the roots of the forest.
+    pub fn is_root(&self, id: usize) -> Result<bool, TreeErr> {
+        Ok(self.tree.parent_of(id)? == Some(TOP))
+    }
+
+    /// Returns the number of children the given node has, without going through them.
+    pub fn child_count(&self, id: usize) -> Result<usize, TreeErr> {
+        self.tree.child_count(id)
+    }
+
+    /// Returns the id of the child of the given id at the index provided, starting from zero.
+    pub fn nth_child(&self, id: usize, n: usize) -> Result<Option<usize>, TreeErr> {
+        self.tree.nth_child(id, n)
+    }
+
+    /// Returns the position of the given id among its siblings starting from zero, the position of a root is among the other roots.
+    pub fn sibling_index(&self, id: usize) -> Result<usize, TreeErr> {
+        self.tree.sibling_index(id)
+    }
+
+    /// Returns a list starting with the id provided followed by each of its ancestors up to and including its root.
+    pub fn path_to_root(&self, id: usize) -> Result<Vec<usize>, TreeErr> {
+        let mut path = self.tree.path_to_root(id)?;
+        path.pop();
+        Ok(path)
+    }
+
+    /// Returns a list of the nodes going from the first node up to the closest ancestor the two nodes share and back down to the second node.
+    /// Fails with InvalidId if the nodes are in different trees of the forest, since there is no path between them.
+    pub fn path_between(&self, from: usize, to: usize) -> Result<Vec<usize>, TreeErr> {
+        let path = self.tree.path_between(from, to)?;
+        if path.contains(&TOP) { return Err(InvalidId) }
+        Ok(path)
+    }
+
+    /// Returns the id of the node after the given id going through the whole forest in the same order as sub_tree, one root after another.
+    pub fn next_node(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        self.tree.next_node(id)
+    }
+
+    /// Returns the id of the node before the given id going through the whole forest in the same order as sub_tree, the first root has none.
+    pub fn prev_node(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        Ok(self.tree.prev_node(id)?.filter(|&node| node != TOP))
+    }
+
+    /// Returns a list of the nodes from the first id up to and including the last id, going through the whole forest in the same order as next_node.
+    /// If the last id comes before the first id the list is empty.
+    pub fn range(&self, first: usize, last: usize) -> Result<Vec<usize>, TreeErr> {
+        self.tree.range(first, last)
+    }
+
+    /// Returns the id of the first node in the sub tree of the given id, in the same order as sub_tree, whose data matches the predicate.
+    pub fn find<F: FnMut(&T) -> bool>(&self, id: usize, predicate: F) -> Result<Option<usize>, TreeErr> {
+        self.tree.find(id, predicate)
+    }
+
+    /// Returns a list of all of the nodes in the sub tree of the given id, in the same order as sub_tree, whose data matches the predicate.
+    pub fn find_all<F: FnMut(&T) -> bool>(&self, id: usize, predicate: F) -> Result<Vec<usize>, TreeErr> {
+        self.tree.find_all(id, predicate)
+    }
+
+    /// Creates a new node containing the data provide and attaches it to the node provide in the position of in_position.
+    /// Siblings of a root become roots themselves.
+    pub fn new_node(&mut self, data: T, in_position: Position, node: usize) -> Result<usize, TreeErr> {
+        self.tree.new_node(data, in_position, node)
+    }
+
+    /// Creates a new node for each of the items provided and attaches them as a run of siblings to the node provided in the position of in_position.
+    pub fn new_nodes<I: IntoIterator<Item = T>>(&mut self, items: I, in_position: Position, node: usize) -> Result<Vec<usize>, TreeErr> {
+        self.tree.new_nodes(items, in_position, node)
+    }
+
+    /// Removes a node from the forest along with all of its descendants.
+    pub fn remove(&mut self, id: usize) -> Result<(), TreeErr> {
+        self.tree.remove(id)
+    }
+
+    /// Removes each of the given nodes along with all of their descendants, skipping ids that have already been removed along with another.
+    pub fn remove_many(&mut self, ids: &[usize]) -> Result<(), TreeErr> {
+        self.tree.remove_many(ids)
+    }
+
+    /// Returns a reference to the data contained by the provided id.
+    pub fn data_at(&self, id: usize) -> Result<&T, TreeErr> {
+        self.tree.data_at(id)
+    }
+
+    /// Returns a mutable reference to the data contained by the provided id.
+    pub fn data_at_mut(&mut self, id: usize) -> Result<&mut T, TreeErr> {
+        self.tree.data_at_mut(id)
+    }
+
+    /// Returns a mutable reference to the data of each of the provided ids at the same time, in the same order.
+    pub fn data_at_many_mut<const N: usize>(&mut self, ids: [usize; N]) -> Result<[&mut T; N], TreeErr> {
+        self.tree.data_at_many_mut(ids)
+    }
+
+    /// Returns the parent id of the given id, roots have no parent.
+    pub fn parent_of(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        Ok(self.tree.parent_of(id)?.filter(|&parent| parent != TOP))
+    }
+
+    /// Returns the next sibling id of the given id.
+    pub fn next_sib_of(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        self.tree.next_sib_of(id)
+    }
+
+    /// Returns the previous sibling id of the given id.
+    pub fn prev_sib_of(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        self.tree.prev_sib_of(id)
+    }
+
+    /// Returns the first child id of the given id.
+    pub fn first_child_of(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        self.tree.first_child_of(id)
+    }
+
+    /// Returns the last child id of the given id.
+    pub fn last_child_of(&self, id: usize) -> Result<Option<usize>, TreeErr> {
+        self.tree.last_child_of(id)
+    }
+
+    /// Moves the given node to be attached to the given node in the position of in_position.
+    pub fn move_to(&mut self, moving: usize, in_position: Position, node: usize) -> Result<(), TreeErr> {
+        self.tree.move_to(moving, in_position, node)
+    }
+}
+
+impl<T: Clone> Forest<T> {
+    /// Clones the given node to be attached to the given node in the position of in_position. Returning the copies new node id.
+    pub fn clone_to(&mut self, cloning: usize, in_position: Position, node: usize) -> Result<usize, TreeErr> {
+        self.tree.clone_to(cloning, in_position, node)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Forest<T> {
+    /// Compares the roots in order, each the same way as structural_eq, ignoring the ids of the nodes.
+    fn eq(&self, other: &Self) -> bool {
+        let (roots, other_roots) = (self.roots(), other.roots());
+
+        self.len() == other.len()
+            && roots.len() == other_roots.len()
+            && roots.into_iter().zip(other_roots).all(|(a, b)| self.tree.sub_tree_eq_by(a, &other.tree, b, |a, b| a == b))
+    }
+}
+
+impl<T: Eq> Eq for Forest<T> {}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Forest<T> {
+    /// Lists the data of each node with its number of children, one root after another in the same order as next_node.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut list = f.debug_list();
+
+        for root in self.roots() {
+            for node in self.tree.sub_tree_info(root).unwrap() {
+                list.entry(&(self.tree.data_at(node.id).unwrap(), node.child_count));
+            }
+        }
+
+        list.finish()
+    }
+}
+
+impl<T> Default for Forest<T> {
+    fn default() -> Self {
+        Forest::new()
+    }
+}
+
+impl<T> From<Tree<T>> for Forest<T> {
+    fn from(tree: Tree<T>) -> Self {
+        Forest::from_trees(Some(tree))
+    }
+}
+
+#[cfg(feature = "bytebuffer")]
+impl<'a, A: IntoBytes<'a>> IntoBytes<'a> for Forest<A> {
+    fn into_bytes(&'a self) -> Box<dyn Iterator<Item = u8> + 'a> {
+        let temp = varint_bytes(self.tree.count_children(TOP));
+
+        Box::new(TreeIter::with_start(&self.tree, temp, self.tree.nodes[TOP].first_child.get(), TOP))
+    }
+}
+
+#[cfg(feature = "bytebuffer")]
+impl<A: FromBytes> FromBytes for Forest<A> {
+    fn from_bytes<T: Iterator<Item = u8>>(bytes: &mut T) -> Result<Self, ByteErr> {
+        let mut forest = Forest::new();
+
+        for _ in 0..varint_from_bytes(bytes)? {
+            let root = forest.add_root(A::from_bytes(bytes)?);
+            forest.tree.from_bytes_helper(root, bytes)?;
+        }
+
+        Ok(forest)
+    }
+
+    fn from_io_bytes<T: Iterator<Item = Result<u8, std::io::Error>>>(bytes: &mut T) -> Result<Self, ByteErr> {
+        let mut forest = Forest::new();
+
+        for _ in 0..varint_from_io_bytes(bytes)? {
+            let root = forest.add_root(A::from_io_bytes(bytes)?);
+            forest.tree.from_io_bytes_helper(root, bytes)?;
+        }
+
+        Ok(forest)
+    }
+}
+
+//the forest is written as the list of its roots, each in the same shape as a tree.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for Forest<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ChildrenRef { tree: &self.tree, id: TOP }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Forest<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let roots = Vec::<NestedNode<T>>::deserialize(deserializer)?;
+        Ok(Forest::from_trees(roots.into_iter().map(Tree::from_nested)))
+    }
+}
@@ -0,0 +1,103 @@
+//! Building trees out of directories on the file system, enabled by the fs feature.
+
+use super::*;
+use std::path::{Path, PathBuf};
+use std::io;
+
+/// A file or directory found while reading a directory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub is_dir: bool,
+    /// The size of the file in bytes.
+    pub len: u64,
+}
+
+impl FileEntry {
+    fn new(path: PathBuf, follow_links: bool) -> io::Result<FileEntry> {
+        let metadata = if follow_links { std::fs::metadata(&path)? } else { std::fs::symlink_metadata(&path)? };
+
+        Ok(FileEntry {
+            name: path.file_name().map_or_else(|| path.to_string_lossy().into_owned(), |name| name.to_string_lossy().into_owned()),
+            is_dir: metadata.is_dir(),
+            len: metadata.len(),
+            path,
+        })
+    }
+}
+
+/// The options for reading a directory into a tree.
+#[derive(Default)]
+pub struct DirOptions {
+    /// The number of levels below the starting directory to read, None reads all of them.
+    pub max_depth: Option<usize>,
+    /// Whether to read the directories that symbolic links point to.
+    pub follow_links: bool,
+    /// Entries that the filter returns false for are left out of the tree along with everything inside of them.
+    pub filter: Option<Box<EntryFilter>>,
+}
+
+type EntryFilter = dyn Fn(&FileEntry) -> bool;
+
+impl Tree<FileEntry> {
+    //reads the directories below the root, keeping the directories still to read on a stack so deep directories don't use up the call stack.
+    //when following links each directory is only read once by its canonical path, so links back up the tree can't loop forever.
+    fn read_dir_children(&mut self, root: usize, options: &DirOptions) -> io::Result<()> {
+        let mut read = HashSet::new();
+        if options.follow_links {
+            read.insert(std::fs::canonicalize(&self.data_of(root).unwrap().path)?);
+        }
+
+        let mut dirs = vec![(root, 1)];
+
+        while let Some((parent, depth)) = dirs.pop() {
+            let mut paths = std::fs::read_dir(&self.data_of(parent).unwrap().path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<_>>>()?;
+            paths.sort();
+
+            let first_dir = dirs.len();
+
+            for path in paths {
+                let entry = FileEntry::new(path, options.follow_links)?;
+
+                if let Some(filter) = &options.filter {
+                    if !filter(&entry) { continue }
+                }
+
+                let mut descend = entry.is_dir && options.max_depth.is_none_or(|max_depth| depth < max_depth);
+                if descend && options.follow_links {
+                    descend = read.insert(std::fs::canonicalize(&entry.path)?);
+                }
+
+                let child = self.get_node(entry);
+                self.append_child(parent, child);
+
+                if descend {
+                    dirs.push((child, depth + 1));
+                }
+            }
+
+            //the first directory is read next, the same order as reading them one inside the other.
+            dirs[first_dir..].reverse();
+        }
+
+        Ok(())
+    }
+
+    /// Creates a tree of the files and directories inside of the directory at the path, with the directory as the root.
+    /// The entries in each directory are sorted by their paths.
+    /// When following links a directory reached a second time, like through a link to one of its parents, is kept as an entry without reading inside of it again.
+    pub fn from_dir<P: AsRef<Path>>(path: P, options: &DirOptions) -> io::Result<Tree<FileEntry>> {
+        let root = FileEntry::new(path.as_ref().to_path_buf(), true)?;
+        let is_dir = root.is_dir;
+        let mut tree = Tree::new_with_root(root);
+
+        if is_dir && options.max_depth != Some(0) {
+            tree.read_dir_children(0, options)?;
+        }
+
+        Ok(tree)
+    }
+}
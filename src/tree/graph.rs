@@ -0,0 +1,43 @@
+//! Conversions between trees and petgraph graphs, enabled by the petgraph feature.
+
+use super::*;
+use petgraph::graph::{DiGraph, NodeIndex};
+
+impl<T> Tree<T> {
+    /// Creates a tree from a directed graph where each edge goes from a parent to its child, the ids in the tree match the graph's node indices.
+    /// Children keep the order of their edges. Fails if the graph is not shaped like a tree, the same way as from_edges.
+    pub fn from_graph<E>(graph: DiGraph<T, E>) -> Result<Tree<T>, TreeErr> {
+        let (nodes, edges) = graph.into_nodes_edges();
+
+        Tree::from_edges(
+            nodes.into_iter().map(|node| node.weight),
+            edges.into_iter().map(|edge| (edge.source().index(), edge.target().index())),
+        )
+    }
+}
+
+impl<T: Clone> Tree<T> {
+    /// Creates a directed graph with an edge from each parent to each of its children.
+    /// Nodes are added in pre-order, and the map gives the graph node index for each id in the tree.
+    pub fn to_graph(&self) -> (DiGraph<T, ()>, HashMap<usize, NodeIndex>) {
+        let mut graph = DiGraph::with_capacity(self.len, self.len.saturating_sub(1));
+        let mut indices = HashMap::with_capacity(self.len);
+
+        if let Some(root) = self.root {
+            let mut current = Some(root);
+
+            while let Some(id) = current {
+                let index = graph.add_node(self.data_of(id).unwrap().clone());
+                indices.insert(id, index);
+
+                if id != root {
+                    graph.add_edge(indices[&self.nodes[id].parent.get().unwrap()], index, ());
+                }
+
+                current = self.next_in_sub_tree(id, root);
+            }
+        }
+
+        (graph, indices)
+    }
+}
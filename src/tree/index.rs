@@ -0,0 +1,73 @@
+//! The integer type the links between nodes are stored as. With the u32_index feature each link takes half the space,
+//! which limits a tree to u32::MAX node slots.
+
+#[cfg(feature = "u32_index")]
+use std::convert::TryFrom;
+#[cfg(not(feature = "u32_index"))]
+use std::num::NonZeroUsize;
+#[cfg(feature = "u32_index")]
+use std::num::NonZeroU32;
+
+#[cfg(not(feature = "u32_index"))]
+pub(super) type Index = usize;
+#[cfg(feature = "u32_index")]
+pub(super) type Index = u32;
+
+//the same as Index but never zero, so that an optional one takes no more space.
+#[cfg(not(feature = "u32_index"))]
+type NonZeroIndex = NonZeroUsize;
+#[cfg(feature = "u32_index")]
+type NonZeroIndex = NonZeroU32;
+
+#[cfg(not(feature = "u32_index"))]
+pub(super) fn narrow(id: usize) -> Index {
+    id
+}
+
+#[cfg(feature = "u32_index")]
+pub(super) fn narrow(id: usize) -> Index {
+    Index::try_from(id).expect("tree has more node slots than the u32_index feature allows")
+}
+
+#[cfg(not(feature = "u32_index"))]
+pub(super) fn widen(index: Index) -> usize {
+    index
+}
+
+#[cfg(feature = "u32_index")]
+pub(super) fn widen(index: Index) -> usize {
+    index as usize
+}
+
+/// An optional id of another node, stored as one more than the id so that None can be zero.
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[derive(Clone, Copy, PartialEq)]
+pub(super) struct Link(Option<NonZeroIndex>);
+
+const _: () = assert!(std::mem::size_of::<Link>() == std::mem::size_of::<Index>());
+
+impl Link {
+    pub const NONE: Link = Link(None);
+
+    pub fn new(id: Option<usize>) -> Link {
+        match id {
+            Some(id) => Link::some(id),
+            None => Link::NONE,
+        }
+    }
+
+    pub fn some(id: usize) -> Link {
+        Link(NonZeroIndex::new(narrow(id + 1)))
+    }
+
+    pub fn get(self) -> Option<usize> {
+        self.0.map(|index| widen(index.get()) - 1)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedLink {
+    pub fn get(&self) -> Option<usize> {
+        self.0.as_ref().map(|index| index.get() as usize - 1)
+    }
+}
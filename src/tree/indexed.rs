@@ -0,0 +1,238 @@
+//! IndexedTree is a tree that keeps indexes of its nodes by keys taken from their data, for finding nodes without searching the tree.
+
+use super::*;
+use std::any::Any;
+
+/// An index from one kind of key to the ids of the nodes with that key, hiding the type of the key.
+trait AnyIndex<T> {
+    /// Returns whether adding the data with the given id would give a unique key to more than one node.
+    fn conflicts(&self, data: &T, id: usize) -> bool;
+    fn insert(&mut self, data: &T, id: usize);
+    fn remove(&mut self, data: &T, id: usize);
+    fn as_any(&self) -> &dyn Any;
+}
+
+struct KeyIndex<T, K> {
+    key: Box<dyn Fn(&T) -> K>,
+    ids: HashMap<K, Vec<usize>>,
+    unique: bool,
+}
+
+impl<T: 'static, K: Eq + Hash + 'static> AnyIndex<T> for KeyIndex<T, K> {
+    fn conflicts(&self, data: &T, id: usize) -> bool {
+        if !self.unique { return false }
+
+        match self.ids.get(&(self.key)(data)) {
+            Some(ids) => ids.iter().any(|&other| other != id),
+            None      => false,
+        }
+    }
+
+    fn insert(&mut self, data: &T, id: usize) {
+        self.ids.entry((self.key)(data)).or_default().push(id);
+    }
+
+    fn remove(&mut self, data: &T, id: usize) {
+        if let Entry::Occupied(mut entry) = self.ids.entry((self.key)(data)) {
+            entry.get_mut().retain(|&other| other != id);
+            if entry.get().is_empty() {
+                entry.remove();
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A tree that keeps track of the ids of its nodes by any number of named indexes, each using keys taken from the data of the nodes.
+/// Unique indexes only allow one node for each key, other indexes allow any number of nodes to share a key.
+/// 
+/// Derefs to the tree it contains for everything that doesn't change the tree, data can only be changed through modify so the indexes are kept up to date.
+/// 
+/// The data has to be 'static since each index is kept as a boxed trait object and downcast back to its key type when looking up a key, which only works for 'static types.
+/// 
+/// ## Functions
+/// - new
+/// - from_tree
+/// 
+/// ## Methods
+/// - add_index
+/// - add_unique_index
+/// - remove_index
+/// - find_by_key
+/// - find_all_by_key
+/// - new_root
+/// - new_node
+/// - remove
+/// - move_to
+/// - modify
+/// - into_tree
+pub struct IndexedTree<T> {
+    tree: Tree<T>,
+    indexes: HashMap<String, Box<dyn AnyIndex<T>>>,
+}
+
+impl<T: 'static> IndexedTree<T> {
+    /// Creates an empty tree without any indexes.
+    pub fn new() -> IndexedTree<T> {
+        IndexedTree::from_tree(Tree::new())
+    }
+
+    /// Creates an indexed tree out of an existing tree without any indexes.
+    pub fn from_tree(tree: Tree<T>) -> IndexedTree<T> {
+        IndexedTree {
+            tree,
+            indexes: HashMap::new(),
+        }
+    }
+
+    fn add_key_index<K, F>(&mut self, name: &str, key: F, unique: bool) -> Result<(), TreeErr>
+    where K: Eq + Hash + 'static, F: Fn(&T) -> K + 'static
+    {
+        let mut index = KeyIndex {
+            key: Box::new(key),
+            ids: HashMap::new(),
+            unique,
+        };
+
+        if let Some(root) = self.tree.get_root() {
+            for id in self.tree.sub_tree(root).unwrap() {
+                let data = self.tree.data_at(id).unwrap();
+                if index.conflicts(data, id) { return Err(DuplicateKey) }
+                index.insert(data, id);
+            }
+        }
+
+        self.indexes.insert(name.to_string(), Box::new(index));
+
+        Ok(())
+    }
+
+    /// Adds an index with the given name using the key provided, replacing any index that already has the name. Any number of nodes can share a key.
+    pub fn add_index<K, F>(&mut self, name: &str, key: F)
+    where K: Eq + Hash + 'static, F: Fn(&T) -> K + 'static
+    {
+        self.add_key_index(name, key, false).unwrap();
+    }
+
+    /// Adds an index with the given name using the key provided, replacing any index that already has the name.
+    /// Fails if two of the nodes already in the tree have the same key.
+    pub fn add_unique_index<K, F>(&mut self, name: &str, key: F) -> Result<(), TreeErr>
+    where K: Eq + Hash + 'static, F: Fn(&T) -> K + 'static
+    {
+        self.add_key_index(name, key, true)
+    }
+
+    /// Removes the index with the given name.
+    pub fn remove_index(&mut self, name: &str) -> Result<(), TreeErr> {
+        self.indexes.remove(name).map(|_| ()).ok_or(UnknownIndex)
+    }
+
+    fn key_index<K: Eq + Hash + 'static>(&self, name: &str) -> Result<&KeyIndex<T, K>, TreeErr> {
+        self.indexes.get(name)
+            .and_then(|index| index.as_any().downcast_ref::<KeyIndex<T, K>>())
+            .ok_or(UnknownIndex)
+    }
+
+    /// Returns the id of the first node with the given key in the named index.
+    /// Fails with UnknownIndex if there is no index with the name using keys of the same type as the key.
+    pub fn find_by_key<K: Eq + Hash + 'static>(&self, name: &str, key: &K) -> Result<Option<usize>, TreeErr> {
+        Ok(self.key_index::<K>(name)?.ids.get(key).and_then(|ids| ids.first().copied()))
+    }
+
+    /// Returns the ids of all of the nodes with the given key in the named index.
+    /// Fails with UnknownIndex if there is no index with the name using keys of the same type as the key.
+    pub fn find_all_by_key<K: Eq + Hash + 'static>(&self, name: &str, key: &K) -> Result<Vec<usize>, TreeErr> {
+        Ok(self.key_index::<K>(name)?.ids.get(key).cloned().unwrap_or_default())
+    }
+
+    fn conflicts(&self, data: &T, id: usize) -> bool {
+        self.indexes.values().any(|index| index.conflicts(data, id))
+    }
+
+    /// Sets the provided data to the new root of the tree removing the old tree.
+    pub fn new_root(&mut self, data: T) -> usize {
+        if let Some(root) = self.tree.get_root() {
+            self.remove(root).unwrap();
+        }
+
+        let id = self.tree.new_root(data);
+
+        for index in self.indexes.values_mut() {
+            index.insert(self.tree.data_at(id).unwrap(), id);
+        }
+
+        id
+    }
+
+    /// Creates a new node containing the data provide and attaches it to the node provide in the position of in_position.
+    /// Fails if a unique index already has a node with the same key.
+    pub fn new_node(&mut self, data: T, in_position: Position, node: usize) -> Result<usize, TreeErr> {
+        if self.conflicts(&data, usize::MAX) { return Err(DuplicateKey) }
+
+        let id = self.tree.new_node(data, in_position, node)?;
+
+        for index in self.indexes.values_mut() {
+            index.insert(self.tree.data_at(id).unwrap(), id);
+        }
+
+        Ok(id)
+    }
+
+    /// Removes a node from the tree along with all of its descendants.
+    pub fn remove(&mut self, id: usize) -> Result<(), TreeErr> {
+        for removing in self.tree.sub_tree(id)? {
+            for index in self.indexes.values_mut() {
+                index.remove(self.tree.data_at(removing).unwrap(), removing);
+            }
+        }
+
+        self.tree.remove(id)
+    }
+
+    /// Moves the given node to be attached to the given node in the position of in_position.
+    pub fn move_to(&mut self, moving: usize, in_position: Position, node: usize) -> Result<(), TreeErr> {
+        self.tree.move_to(moving, in_position, node)
+    }
+
+    /// Changes the data of the given node with the function provided, updating its keys afterwards.
+    /// If a new key is already used by another node in a unique index the change is undone and DuplicateKey is returned.
+    pub fn modify<F: FnOnce(&mut T)>(&mut self, id: usize, change: F) -> Result<(), TreeErr> where T: Clone {
+        let old = self.tree.data_at(id)?.clone();
+
+        change(self.tree.data_at_mut(id).unwrap());
+
+        if self.conflicts(self.tree.data_at(id).unwrap(), id) {
+            *self.tree.data_at_mut(id).unwrap() = old;
+            return Err(DuplicateKey);
+        }
+
+        for index in self.indexes.values_mut() {
+            index.remove(&old, id);
+            index.insert(self.tree.data_at(id).unwrap(), id);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the tree without the indexes.
+    pub fn into_tree(self) -> Tree<T> {
+        self.tree
+    }
+}
+
+impl<T: 'static> Default for IndexedTree<T> {
+    fn default() -> Self {
+        IndexedTree::new()
+    }
+}
+
+impl<T> std::ops::Deref for IndexedTree<T> {
+    type Target = Tree<T>;
+
+    fn deref(&self) -> &Tree<T> {
+        &self.tree
+    }
+}
@@ -0,0 +1,30 @@
+//! JSON helpers, enabled by the json feature.
+//!
+//! Trees are written as their root node, or null for an empty tree. Each node is an object with two members, "data" holding the
+//! node's data as serde writes it and "children" holding an array of the node's children in order:
+//!
+//! ```text
+//! {"data": "root", "children": [
+//!     {"data": "1st root child", "children": []},
+//!     {"data": "2nd root child", "children": []}
+//! ]}
+//! ```
+//!
+//! serde_json stops reading after 128 levels of nesting, use FlatTree for deeper trees.
+
+use super::*;
+use serde::{Serialize, de::DeserializeOwned};
+
+impl<T: Serialize> Tree<T> {
+    /// Writes the tree as a JSON string of nested nodes.
+    pub fn to_json_string(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+impl<T: DeserializeOwned> Tree<T> {
+    /// Reads a tree from a JSON string of nested nodes.
+    pub fn from_json_str(json: &str) -> serde_json::Result<Tree<T>> {
+        serde_json::from_str(json)
+    }
+}
@@ -0,0 +1,154 @@
+//! LazyTree reads a tree from bytes one level at a time, only reading the children of a node when they are asked for.
+
+use super::*;
+
+/// The ways expanding a node of a lazy tree can fail.
+#[derive(Debug)]
+pub enum LazyErr {
+    /// The bytes of the children couldn't be read.
+    Bytes(ByteErr),
+    Tree(TreeErr),
+}
+
+impl From<ByteErr> for LazyErr {
+    fn from(err: ByteErr) -> Self {
+        LazyErr::Bytes(err)
+    }
+}
+
+impl From<TreeErr> for LazyErr {
+    fn from(err: TreeErr) -> Self {
+        LazyErr::Tree(err)
+    }
+}
+
+/// A tree read from the bytes made by into_bytes, where only the root is read at first and the children of each node are read when the node is expanded.
+/// The data of nodes that are never expanded is never kept, though reading past a sibling still has to step over the bytes of its sub tree unless the bytes have offsets.
+/// 
+/// Derefs to the part of the tree that has been read so far, where nodes that haven't been expanded don't have any children yet.
+/// 
+/// ## Functions
+/// - new
+/// - with_offsets
+/// 
+/// ## Methods
+/// - expand
+/// - is_expanded
+/// - into_tree
+pub struct LazyTree<'b, A> {
+    bytes: &'b [u8],
+    //whether the bytes were written by into_offset_bytes.
+    offsets: bool,
+    tree: Tree<A>,
+    //the offset of the first child and the number of children of each node that hasn't been expanded.
+    unexpanded: HashMap<usize, (usize, usize)>,
+}
+
+impl<'b, A: FromBytes> LazyTree<'b, A> {
+    //returns the offset after the sub trees of the given number of nodes starting at the offset.
+    fn skip(&self, offset: usize, count: usize) -> Result<usize, ByteErr> {
+        read_at_offset(self.bytes, offset, |bytes| Tree::<A>::skip_from_bytes(bytes, count)).map(|(_, offset)| offset)
+    }
+
+    //reads the node starting at the offset, returning its data, its child count, the offset of its first child and the offset after its sub tree if the bytes have offsets.
+    fn read_node(&self, offset: usize) -> Result<(A, usize, usize, Option<usize>), ByteErr> {
+        let (end, offset) = if self.offsets {
+            let (len, offset) = read_at_offset(self.bytes, offset, varint_from_bytes)?;
+            (Some(offset.saturating_add(len)), offset)
+        }
+        else { (None, offset) };
+
+        let (data, offset) = read_at_offset(self.bytes, offset, A::from_bytes)?;
+        let (child_count, offset) = read_at_offset(self.bytes, offset, varint_from_bytes)?;
+
+        Ok((data, child_count, offset, end))
+    }
+
+    fn open(bytes: &'b [u8], offsets: bool) -> Result<LazyTree<'b, A>, ByteErr> {
+        let mut lazy = LazyTree {
+            bytes,
+            offsets,
+            tree: Tree::new(),
+            unexpanded: HashMap::new(),
+        };
+
+        let (node_count, offset) = read_at_offset(bytes, 0, varint_from_bytes)?;
+        if node_count > 0 {
+            let (data, child_count, children, _) = lazy.read_node(offset)?;
+
+            let root = lazy.tree.new_root(data);
+            if child_count > 0 {
+                lazy.unexpanded.insert(root, (children, child_count));
+            }
+        }
+
+        Ok(lazy)
+    }
+
+    /// Reads the root of the tree from the bytes, leaving the rest of the tree unread.
+    pub fn new(bytes: &'b [u8]) -> Result<LazyTree<'b, A>, ByteErr> {
+        LazyTree::open(bytes, false)
+    }
+
+    /// Reads the root of the tree from bytes written by into_offset_bytes, which lets expanding a node jump over the sub trees of its children instead of reading them.
+    pub fn with_offsets(bytes: &'b [u8]) -> Result<LazyTree<'b, A>, ByteErr> {
+        LazyTree::open(bytes, true)
+    }
+
+    /// Reads the children of the node if they haven't been read yet, and returns their ids.
+    /// If reading the children fails the node is left unexpanded, so expanding it can be tried again.
+    pub fn expand(&mut self, id: usize) -> Result<Vec<usize>, LazyErr> {
+        self.tree.valid_node(id)?;
+
+        if let Some(&(mut offset, child_count)) = self.unexpanded.get(&id) {
+            //all of the children are read before any are attached, so a failed read doesn't leave the node half expanded.
+            let mut children = Vec::new();
+            for i in 0..child_count {
+                let (data, grandchild_count, grandchildren, end) = self.read_node(offset)?;
+
+                //the next child starts after this child's sub tree, there's no need to find it after the last child.
+                if i + 1 < child_count {
+                    offset = match end {
+                        Some(end) => end,
+                        None => self.skip(grandchildren, grandchild_count)?,
+                    };
+                }
+
+                children.push((data, grandchild_count, grandchildren));
+            }
+
+            self.unexpanded.remove(&id);
+            for (data, grandchild_count, grandchildren) in children {
+                let child = self.tree.get_node(data);
+                self.tree.append_child(id, child);
+                if grandchild_count > 0 {
+                    self.unexpanded.insert(child, (grandchildren, grandchild_count));
+                }
+            }
+        }
+
+        Ok(self.tree.children_of(id)?)
+    }
+
+    /// Returns true if the children of the node have been read, nodes without children always count as expanded.
+    pub fn is_expanded(&self, id: usize) -> bool {
+        !self.unexpanded.contains_key(&id)
+    }
+
+    /// Reads the rest of the tree and returns it.
+    pub fn into_tree(mut self) -> Result<Tree<A>, LazyErr> {
+        while let Some(&id) = self.unexpanded.keys().next() {
+            self.expand(id)?;
+        }
+
+        Ok(self.tree)
+    }
+}
+
+impl<'b, A> std::ops::Deref for LazyTree<'b, A> {
+    type Target = Tree<A>;
+
+    fn deref(&self) -> &Tree<A> {
+        &self.tree
+    }
+}
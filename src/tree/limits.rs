@@ -0,0 +1,71 @@
+//! Reading the byte format from untrusted sources, with limits on how big the tree can get.
+
+use super::*;
+
+/// The limits on the tree read by from_bytes_limited, None means no limit.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct ReadLimits {
+    /// The most nodes the tree can have, including the root.
+    pub max_nodes: Option<usize>,
+    /// The most levels the tree can have below the root.
+    pub max_depth: Option<usize>,
+}
+
+/// The ways reading bytes with limits can fail.
+#[derive(Debug)]
+pub enum LimitErr {
+    /// The bytes couldn't be read as a tree.
+    Bytes(ByteErr),
+    /// The tree has more nodes than max_nodes.
+    TooManyNodes,
+    /// The tree goes deeper than max_depth.
+    TooDeep,
+}
+
+impl From<ByteErr> for LimitErr {
+    fn from(err: ByteErr) -> Self {
+        LimitErr::Bytes(err)
+    }
+}
+
+impl<A: FromBytes> Tree<A> {
+    //reads the tree without recursing, keeping the parents that still have children to read on a stack so the depth can be checked.
+    fn read_limited<I>(bytes: &mut I, limits: &ReadLimits, read_data: fn(&mut I) -> Result<A, ByteErr>, read_count: fn(&mut I) -> Result<usize, ByteErr>) -> Result<Tree<A>, LimitErr> {
+        let node_count = read_count(bytes)?;
+        if node_count == 0 { return Ok(Tree::new()) }
+        if limits.max_nodes == Some(0) { return Err(LimitErr::TooManyNodes) }
+
+        //the header can't be trusted, so the room is capped the same way as reading without limits, and never more than max_nodes.
+        let mut tree = Tree::with_header_capacity(node_count.min(limits.max_nodes.unwrap_or(usize::MAX)), None);
+        let root = tree.new_root(read_data(bytes)?);
+        let mut parents = vec![(root, read_count(bytes)?)];
+
+        while let Some((parent, remaining)) = parents.last_mut() {
+            if *remaining == 0 {
+                parents.pop();
+                continue;
+            }
+            *remaining -= 1;
+            let parent = *parent;
+
+            if limits.max_depth.is_some_and(|max_depth| parents.len() > max_depth) { return Err(LimitErr::TooDeep) }
+            if limits.max_nodes.is_some_and(|max_nodes| tree.len() >= max_nodes) { return Err(LimitErr::TooManyNodes) }
+
+            let child = tree.get_node(read_data(bytes)?);
+            tree.append_child(parent, child);
+            parents.push((child, read_count(bytes)?));
+        }
+
+        Ok(tree)
+    }
+
+    /// Reads a tree like from_bytes, but fails as soon as the tree goes past the limits instead of reading all of it.
+    pub fn from_bytes_limited<T: Iterator<Item = u8>>(bytes: &mut T, limits: &ReadLimits) -> Result<Tree<A>, LimitErr> {
+        Tree::read_limited(bytes, limits, A::from_bytes, varint_from_bytes)
+    }
+
+    /// Reads a tree like from_io_bytes, but fails as soon as the tree goes past the limits instead of reading all of it.
+    pub fn from_io_bytes_limited<T: Iterator<Item = Result<u8, std::io::Error>>>(bytes: &mut T, limits: &ReadLimits) -> Result<Tree<A>, LimitErr> {
+        Tree::read_limited(bytes, limits, A::from_io_bytes, varint_from_io_bytes)
+    }
+}
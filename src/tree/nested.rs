@@ -0,0 +1,97 @@
+//! NestedNode is the owned recursive form of a tree, where each node holds its children directly.
+
+use super::*;
+
+/// A node that owns all of its children, for converting trees to and from the recursive shape used by other crates and formats.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NestedNode<T> {
+    pub data: T,
+    pub children: Vec<NestedNode<T>>,
+}
+
+impl<T> NestedNode<T> {
+    /// Creates a node without children.
+    pub fn new(data: T) -> NestedNode<T> {
+        NestedNode {
+            data,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl<T> Tree<T> {
+    //attaches the nested children under the parent, keeping the children left to attach at each level on a stack so deep nesting doesn't use up the call stack.
+    fn read_nested_children(&mut self, parent: usize, children: Vec<NestedNode<T>>) {
+        let mut levels = vec![(parent, children.into_iter())];
+
+        while let Some((parent, children)) = levels.last_mut() {
+            let parent = *parent;
+
+            match children.next() {
+                Some(child) => {
+                    let id = self.get_node(child.data);
+                    self.append_child(parent, id);
+                    levels.push((id, child.children.into_iter()));
+                }
+                None => { levels.pop(); }
+            }
+        }
+    }
+
+    /// Creates a tree with the same shape and data as the nested node.
+    pub fn from_nested(nested: NestedNode<T>) -> Tree<T> {
+        let mut tree = Tree::new_with_root(nested.data);
+        tree.read_nested_children(0, nested.children);
+        tree
+    }
+
+    //builds nested nodes in the shape of the sub tree with the data given for each id.
+    //the nodes still being built are kept on a stack with the next child each is waiting on, so deep trees don't use up the call stack.
+    fn build_nested<D, F: FnMut(usize) -> D>(&self, id: usize, mut data: F) -> NestedNode<D> {
+        let mut building = vec![(NestedNode::new(data(id)), self.nodes[id].first_child.get())];
+
+        loop {
+            let (_, child) = building.last_mut().unwrap();
+
+            match *child {
+                Some(child_id) => {
+                    *child = self.nodes[child_id].next_sib.get();
+                    building.push((NestedNode::new(data(child_id)), self.nodes[child_id].first_child.get()));
+                }
+                None => {
+                    let (node, _) = building.pop().unwrap();
+                    match building.last_mut() {
+                        Some((parent, _)) => parent.children.push(node),
+                        None => return node,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Turns the tree into nested nodes with the same shape and data, returning None if the tree is empty.
+    pub fn into_nested(mut self) -> Option<NestedNode<T>> {
+        let root = self.root?;
+
+        let mut data: Vec<Option<T>> = self.nodes.iter().map(|_| None).collect();
+        for (owned, &owner) in std::mem::take(&mut self.data).into_iter().zip(&self.owners) {
+            data[widen(owner)] = Some(owned);
+        }
+
+        Some(self.build_nested(root, |id| data[id].take().unwrap()))
+    }
+}
+
+impl<T: Clone> Tree<T> {
+    /// Returns nested nodes with the same shape and a copy of the data of the tree, returning None if the tree is empty.
+    pub fn to_nested(&self) -> Option<NestedNode<T>> {
+        Some(self.build_nested(self.root?, |id| self.data_of(id).unwrap().clone()))
+    }
+}
+
+impl<T> From<NestedNode<T>> for Tree<T> {
+    fn from(nested: NestedNode<T>) -> Self {
+        Tree::from_nested(nested)
+    }
+}
@@ -0,0 +1,177 @@
+//! Newick import and export, enabled by the newick feature. Each node is written as its children in parentheses followed by its label
+//! and branch length, like `((a:1,b:2)c:0.5,d)e;`.
+
+use super::*;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// The label and branch length of a node, made from the data of a node when writing newick and turned into the data of a node when reading it.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct NewickNode {
+    pub label: String,
+    /// The length of the branch to the node's parent.
+    pub length: Option<f64>,
+}
+
+impl NewickNode {
+    /// Creates a node with the label and no branch length.
+    pub fn new<S: Into<String>>(label: S) -> NewickNode {
+        NewickNode {
+            label: label.into(),
+            length: None,
+        }
+    }
+}
+
+const DELIMITERS: &[char] = &['(', ')', '[', ']', '\'', ':', ';', ','];
+
+//skips whitespace and comments in square brackets.
+fn skip(chars: &mut Peekable<Chars>) -> Result<(), TreeErr> {
+    while let Some(&c) = chars.peek() {
+        if c == '[' {
+            while chars.next().ok_or(InvalidNesting)? != ']' {}
+        }
+        else if c.is_whitespace() { chars.next(); }
+        else { break }
+    }
+    Ok(())
+}
+
+//reads a label in single quotes with doubled quotes as escapes, or a plain label where underscores stand for spaces.
+fn read_label(chars: &mut Peekable<Chars>) -> Result<String, TreeErr> {
+    let mut label = String::new();
+
+    if chars.peek() == Some(&'\'') {
+        chars.next();
+        loop {
+            match chars.next().ok_or(InvalidNesting)? {
+                '\'' if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    label.push('\'');
+                }
+                '\'' => break,
+                c => label.push(c),
+            }
+        }
+    }
+    else {
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || DELIMITERS.contains(&c) { break }
+            label.push(if c == '_' { ' ' } else { c });
+            chars.next();
+        }
+    }
+
+    Ok(label)
+}
+
+fn push_label(newick: &mut String, label: &str) {
+    if !label.chars().any(|c| c.is_whitespace() || c == '_' || DELIMITERS.contains(&c)) {
+        newick.push_str(label);
+        return
+    }
+
+    newick.push('\'');
+    newick.push_str(&label.replace('\'', "''"));
+    newick.push('\'');
+}
+
+impl<T> Tree<T> {
+    fn to_newick_helper<F: FnMut(&T) -> NewickNode>(&self, id: usize, newick: &mut String, render: &mut F) {
+        self.walk_sub_tree(id, |node_id, visit| {
+            let has_children = self.nodes[node_id].first_child.get().is_some();
+
+            if visit == Visit::Enter {
+                if node_id != id && self.nodes[node_id].prev_sib.get().is_some() { newick.push(',') }
+                if has_children { newick.push('(') }
+                return
+            }
+
+            //the label comes after the children.
+            if has_children { newick.push(')') }
+
+            let node = render(self.data_of(node_id).unwrap());
+            push_label(newick, &node.label);
+            if let Some(length) = node.length {
+                newick.push(':');
+                newick.push_str(&length.to_string());
+            }
+        });
+    }
+
+    /// Writes the tree in the newick format, with the label and branch length of each node made by render. An empty tree writes an empty string.
+    pub fn to_newick<F: FnMut(&T) -> NewickNode>(&self, mut render: F) -> String {
+        let mut newick = String::new();
+
+        if let Some(root) = self.root {
+            self.to_newick_helper(root, &mut newick, &mut render);
+            newick.push(';');
+        }
+
+        newick
+    }
+
+    /// Creates a tree from text in the newick format, with the data of each node made from its label and branch length by make_node.
+    /// make_node is called once all of a node's children are read, so children are made before their parents.
+    /// Fails with InvalidNesting if the text isn't valid newick, and MultipleRoots if there is more than one tree in the text.
+    pub fn from_newick<F: FnMut(NewickNode) -> T>(text: &str, mut make_node: F) -> Result<Tree<T>, TreeErr> {
+        let mut chars = text.chars().peekable();
+        //the children read so far of each open parenthesis.
+        let mut open: Vec<Vec<NestedNode<T>>> = Vec::new();
+
+        skip(&mut chars)?;
+        if chars.peek().is_none() { return Ok(Tree::new()) }
+
+        let root = 'nodes: loop {
+            skip(&mut chars)?;
+            while chars.peek() == Some(&'(') {
+                chars.next();
+                open.push(Vec::new());
+                skip(&mut chars)?;
+            }
+
+            let mut children = Vec::new();
+            loop {
+                skip(&mut chars)?;
+                let label = read_label(&mut chars)?;
+                skip(&mut chars)?;
+
+                let mut length = None;
+                if chars.peek() == Some(&':') {
+                    chars.next();
+                    skip(&mut chars)?;
+
+                    let mut number = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c.is_whitespace() || DELIMITERS.contains(&c) { break }
+                        number.push(c);
+                        chars.next();
+                    }
+                    length = Some(number.parse().map_err(|_| InvalidNesting)?);
+                    skip(&mut chars)?;
+                }
+
+                let node = NestedNode { data: make_node(NewickNode { label, length }), children };
+
+                match chars.next() {
+                    Some(',') => {
+                        open.last_mut().ok_or(InvalidNesting)?.push(node);
+                        continue 'nodes
+                    }
+                    Some(')') => {
+                        let mut siblings = open.pop().ok_or(InvalidNesting)?;
+                        siblings.push(node);
+                        children = siblings;
+                    }
+                    Some(';') | None if open.is_empty() => break 'nodes node,
+                    _ => return Err(InvalidNesting),
+                }
+            }
+        };
+
+        skip(&mut chars)?;
+        if chars.peek().is_some() { return Err(MultipleRoots) }
+
+        Ok(Tree::from_nested(root))
+    }
+}
@@ -0,0 +1,91 @@
+//! The offset byte format, which is the normal byte format with the byte length of each sub tree written before it.
+//! Readers can jump over a sub tree using its length instead of reading all of it, so a node deep in the bytes can be found without reading the nodes before it.
+
+use super::*;
+
+impl<'a, A: IntoBytes<'a>> Tree<A> {
+    /// Returns the bytes of the tree in the offset format, where every sub tree starts with its length in bytes.
+    /// The lengths are only known once the whole tree has been turned into bytes, so the bytes are collected instead of given as an iterator.
+    pub fn into_offset_bytes(&'a self) -> Vec<u8> {
+        let root = match self.root {
+            Some(root) => root,
+            None => return varint_bytes(0).collect(),
+        };
+
+        let mut ids = Vec::with_capacity(self.len);
+        let mut current = Some(root);
+        while let Some(id) = current {
+            ids.push(id);
+            current = self.next_in_sub_tree(id, root);
+        }
+
+        //the bytes of each node without its children, then the length of its sub tree once its children have been added on.
+        let mut data: Vec<Vec<u8>> = Vec::with_capacity(ids.len());
+        let mut lens = vec![0; self.nodes.len()];
+        for &id in &ids {
+            let mut bytes: Vec<u8> = self.data_of(id).unwrap().into_bytes().collect();
+            bytes.extend(varint_bytes(self.count_children(id)));
+            lens[id] = bytes.len();
+            data.push(bytes);
+        }
+
+        //going backwards every child is finished before its parent is.
+        for &id in ids.iter().rev() {
+            if id == root { break }
+            let parent = self.nodes[id].parent.get().unwrap();
+            lens[parent] += varint_len(lens[id]) + lens[id];
+        }
+
+        let mut bytes: Vec<u8> = varint_bytes(self.len).collect();
+        bytes.reserve(varint_len(lens[root]) + lens[root]);
+        for (&id, data) in ids.iter().zip(data) {
+            bytes.extend(varint_bytes(lens[id]));
+            bytes.extend(data);
+        }
+
+        bytes
+    }
+}
+
+impl<A: FromBytes> Tree<A> {
+    /// Reads a whole tree written by into_offset_bytes.
+    pub fn from_offset_bytes<T: Iterator<Item = u8>>(bytes: &mut T) -> Result<Tree<A>, ByteErr> {
+        let node_count = varint_from_bytes(bytes)?;
+        let mut tree = Tree::with_header_capacity(node_count, bytes.size_hint().1);
+
+        if node_count > 0 {
+            varint_from_bytes(bytes)?;
+            let root = tree.new_root(A::from_bytes(bytes)?);
+            //the lengths are only needed for skipping, so they're read past.
+            tree.read_sub_trees(root, bytes, |bytes| {
+                varint_from_bytes(bytes)?;
+                A::from_bytes(bytes)
+            }, varint_from_bytes)?;
+        }
+
+        tree.check_node_count(node_count)
+    }
+
+    /// Reads only the data of the node at the path from bytes written by into_offset_bytes, where the path is the child index at each level below the root like node_at_path.
+    /// The siblings before each step are jumped over without being read, only the nodes on the path are. Returns None if there is no node at the path.
+    pub fn read_at_path(bytes: &[u8], path: &[usize]) -> Result<Option<A>, ByteErr> {
+        let (node_count, mut offset) = read_at_offset(bytes, 0, varint_from_bytes)?;
+        if node_count == 0 { return Ok(None) }
+
+        for &index in path {
+            let (_, after_len) = read_at_offset(bytes, offset, varint_from_bytes)?;
+            let (_, after_data) = read_at_offset(bytes, after_len, A::from_bytes)?;
+            let (child_count, mut child) = read_at_offset(bytes, after_data, varint_from_bytes)?;
+            if index >= child_count { return Ok(None) }
+
+            for _ in 0..index {
+                let (len, after_len) = read_at_offset(bytes, child, varint_from_bytes)?;
+                child = after_len.saturating_add(len);
+            }
+            offset = child;
+        }
+
+        let (_, after_len) = read_at_offset(bytes, offset, varint_from_bytes)?;
+        Ok(Some(read_at_offset(bytes, after_len, A::from_bytes)?.0))
+    }
+}
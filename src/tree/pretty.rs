@@ -0,0 +1,107 @@
+//! Drawing trees as text with box drawing characters.
+
+use super::*;
+use std::fmt::{self, Display};
+use std::io;
+
+/// The options for pretty printing a tree.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrettyOptions {
+    /// The number of characters each level is indented by. Indents below 2 are drawn as 2, the room the branch and the space after it take.
+    pub indent: usize,
+    /// The number of levels below the root, or the node given to pretty_print_from, to print. None prints all of them.
+    pub max_depth: Option<usize>,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        PrettyOptions {
+            indent: 4,
+            max_depth: None,
+        }
+    }
+}
+
+//passes the text on to an io::Write as it is written, keeping the io error since fmt::Error can't carry it.
+struct IoAdapter<'a, W> {
+    write: &'a mut W,
+    err: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        self.write.write_all(text.as_bytes()).map_err(|err| {
+            self.err = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+impl<T> Tree<T> {
+    //draws the descendants of the node, keeping the next child to draw at each level on a stack along with the length of the prefix for that level.
+    fn pretty_print_helper<W: fmt::Write, D: Display, F: FnMut(&T) -> D>(&self, id: usize, write: &mut W, options: &PrettyOptions, render: &mut F) -> fmt::Result {
+        if options.max_depth == Some(0) { return Ok(()) }
+
+        let indent = options.indent.max(2);
+        let mut prefix = String::new();
+        let mut levels = vec![(self.nodes[id].first_child.get(), 0)];
+
+        while let Some((child, prefix_len)) = levels.last_mut() {
+            let child_id = match *child {
+                Some(child_id) => child_id,
+                None => {
+                    levels.pop();
+                    continue
+                }
+            };
+            prefix.truncate(*prefix_len);
+            *child = self.nodes[child_id].next_sib.get();
+
+            let (branch, continued) = if child.is_none() { ('└', ' ') } else { ('├', '│') };
+            writeln!(write, "{}{}{} {}", prefix, branch, "─".repeat(indent - 2), render(self.data_of(child_id).unwrap()))?;
+
+            if options.max_depth.is_none_or(|max_depth| levels.len() < max_depth) {
+                prefix.push(continued);
+                prefix.push_str(&" ".repeat(indent - 1));
+                levels.push((self.nodes[child_id].first_child.get(), prefix.len()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draws the tree with box drawing characters starting from the root, with each node on its own line made by render.
+    pub fn pretty_print<W: fmt::Write, D: Display, F: FnMut(&T) -> D>(&self, write: &mut W, options: &PrettyOptions, render: F) -> fmt::Result {
+        match self.root {
+            Some(root) => self.pretty_print_from(root, write, options, render).unwrap(),
+            None => Ok(()),
+        }
+    }
+
+    /// Draws the sub tree of the given id the same way as pretty_print, with the given node on the first line.
+    /// Fails with InvalidId before anything is written if the id isn't in the tree, otherwise gives the result of writing.
+    pub fn pretty_print_from<W: fmt::Write, D: Display, F: FnMut(&T) -> D>(&self, id: usize, write: &mut W, options: &PrettyOptions, mut render: F) -> Result<fmt::Result, TreeErr> {
+        self.valid_node(id)?;
+
+        Ok(writeln!(write, "{}", render(self.data_of(id).unwrap()))
+            .and_then(|_| self.pretty_print_helper(id, write, options, &mut render)))
+    }
+
+    /// The same as pretty_print, but writes to an io::Write as each line is drawn.
+    /// A node that fails to render gives an io::Error of kind Other.
+    pub fn pretty_print_io<W: io::Write, D: Display, F: FnMut(&T) -> D>(&self, write: &mut W, options: &PrettyOptions, render: F) -> io::Result<()> {
+        match self.root {
+            Some(root) => self.pretty_print_io_from(root, write, options, render).unwrap(),
+            None => Ok(()),
+        }
+    }
+
+    /// The same as pretty_print_from, but writes to an io::Write as each line is drawn.
+    pub fn pretty_print_io_from<W: io::Write, D: Display, F: FnMut(&T) -> D>(&self, id: usize, write: &mut W, options: &PrettyOptions, render: F) -> Result<io::Result<()>, TreeErr> {
+        let mut adapter = IoAdapter { write, err: None };
+
+        Ok(self.pretty_print_from(id, &mut adapter, options, render)?.map_err(|_| {
+            adapter.err.take().unwrap_or_else(|| io::Error::other("a node failed to render"))
+        }))
+    }
+}
@@ -0,0 +1,32 @@
+//! Random tree generation for tests and benchmarks, enabled by the testing feature.
+
+use super::*;
+use rand::Rng;
+
+impl<T> Tree<T> {
+    /// Creates a tree with up to node_count nodes, where each new node is added as the last child of a random node with fewer than max_branching children.
+    /// The data of each node is made by gen_data in the order the nodes are added. Only the root is made if max_branching is 0.
+    pub fn random<R: Rng + ?Sized, F: FnMut(&mut R) -> T>(rng: &mut R, node_count: usize, max_branching: usize, mut gen_data: F) -> Tree<T> {
+        let mut tree = Tree::new();
+        if node_count == 0 { return tree }
+
+        let root = tree.get_node(gen_data(rng));
+        tree.root = Some(root);
+
+        //the nodes that can still take another child, along with how many children they have.
+        let mut open = Vec::new();
+        if max_branching > 0 { open.push((root, 0)) }
+
+        while tree.len < node_count && !open.is_empty() {
+            let index = rng.gen_range(0..open.len());
+            let id = tree.get_node(gen_data(rng));
+            tree.append_child(open[index].0, id);
+
+            open[index].1 += 1;
+            if open[index].1 == max_branching { open.swap_remove(index); }
+            open.push((id, 0));
+        }
+
+        tree
+    }
+}
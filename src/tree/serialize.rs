@@ -0,0 +1,181 @@
+//! Serde support, enabled by the serde feature. Trees are written as their root node, where each node is a struct of its data
+//! and a list of its children, the same shape as NestedNode. An empty tree is written as none.
+//!
+//! FlatTree wraps a tree to write it as a flat list of nodes instead, which keeps the ids and doesn't recurse.
+
+use super::*;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::ser::{SerializeStruct, SerializeSeq};
+
+//borrows a node of the tree so it can be written without copying the tree into nested nodes first.
+struct NestedRef<'a, T> {
+    tree: &'a Tree<T>,
+    id: usize,
+}
+
+//borrows the children of a node, also used to write the roots of a forest.
+pub(super) struct ChildrenRef<'a, T> {
+    pub(super) tree: &'a Tree<T>,
+    pub(super) id: usize,
+}
+
+impl<'a, T: Serialize> Serialize for NestedRef<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("NestedNode", 2)?;
+        state.serialize_field("data", self.tree.data_of(self.id).unwrap())?;
+        state.serialize_field("children", &ChildrenRef { tree: self.tree, id: self.id })?;
+        state.end()
+    }
+}
+
+impl<'a, T: Serialize> Serialize for ChildrenRef<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_seq(Some(self.tree.count_children(self.id)))?;
+        let mut child = self.tree.nodes[self.id].first_child.get();
+
+        while let Some(child_id) = child {
+            state.serialize_element(&NestedRef { tree: self.tree, id: child_id })?;
+            child = self.tree.nodes[child_id].next_sib.get();
+        }
+
+        state.end()
+    }
+}
+
+impl<T: Serialize> Serialize for Tree<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.root {
+            Some(root) => serializer.serialize_some(&NestedRef { tree: self, id: root }),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Tree<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::<NestedNode<T>>::deserialize(deserializer)? {
+            Some(nested) => Tree::from_nested(nested),
+            None => Tree::new(),
+        })
+    }
+}
+
+/// Wraps a tree so that serde writes it as a struct with a list of nodes in the same order as sub_tree, where each node is a struct
+/// of its id, the id of its parent and its data. The ids of the nodes are kept when read back, and reading or writing doesn't recurse
+/// so very deep trees can't overflow the stack. Reading fails if the ids need more than 8 slots for each node, so compact the tree first if
+/// it has had most of its nodes removed.
+#[derive(Debug, PartialEq)]
+pub struct FlatTree<T>(pub Tree<T>);
+
+impl<T> From<Tree<T>> for FlatTree<T> {
+    fn from(tree: Tree<T>) -> Self {
+        FlatTree(tree)
+    }
+}
+
+impl<T> From<FlatTree<T>> for Tree<T> {
+    fn from(flat: FlatTree<T>) -> Self {
+        flat.0
+    }
+}
+
+#[derive(Serialize)]
+struct FlatNodeRef<'a, T> {
+    id: usize,
+    parent: Option<usize>,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct FlatNode<T> {
+    id: usize,
+    parent: Option<usize>,
+    data: T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "FlatTree")]
+struct FlatNodes<T> {
+    nodes: Vec<FlatNode<T>>,
+}
+
+//borrows the nodes of the tree to write them in order without recursion.
+struct FlatNodesRef<'a, T>(&'a Tree<T>);
+
+impl<'a, T: Serialize> Serialize for FlatNodesRef<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let tree = self.0;
+        let mut state = serializer.serialize_seq(Some(tree.len))?;
+
+        if let Some(root) = tree.root {
+            let mut current = Some(root);
+
+            while let Some(id) = current {
+                state.serialize_element(&FlatNodeRef {
+                    id,
+                    parent: tree.nodes[id].parent.get(),
+                    data: tree.data_of(id).unwrap(),
+                })?;
+
+                current = tree.next_in_sub_tree(id, root);
+            }
+        }
+
+        state.end()
+    }
+}
+
+impl<T: Serialize> Serialize for FlatTree<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("FlatTree", 1)?;
+        state.serialize_field("nodes", &FlatNodesRef(&self.0))?;
+        state.end()
+    }
+}
+
+impl<T> Tree<T> {
+    //rebuilds the tree from nodes listed with their parents before them, keeping their ids. Unused ids become free slots.
+    //Fails with OutOfRange if the ids leave too many free slots for the number of nodes listed.
+    fn from_listed_nodes(listed: Vec<(usize, Option<usize>, T)>) -> Result<Tree<T>, TreeErr> {
+        let slot_count = Tree::<T>::listed_slot_count(listed.iter().map(|&(id, _, _)| id), listed.len())?;
+        let mut tree = Tree {
+            nodes: vec![Slot::Free(Link::NONE); slot_count],
+            data: Vec::with_capacity(listed.len()),
+            owners: Vec::with_capacity(listed.len()),
+            free: None,
+            root: None,
+            len: slot_count,
+        };
+
+        for (id, parent, data) in listed {
+            if tree.data_of(id).is_some() { return Err(OverlappingIds) }
+            tree.put_data(id, data);
+
+            match parent {
+                //the parent has to be listed first.
+                Some(parent) if parent >= slot_count || tree.data_of(parent).is_none() || parent == id => return Err(InvalidId),
+                Some(parent) => tree.append_child(parent, id),
+                None if tree.root.is_some() => return Err(MultipleRoots),
+                None => tree.root = Some(id),
+            }
+        }
+
+        for id in (0..slot_count).rev() {
+            if tree.data_of(id).is_none() {
+                tree.push_free(id);
+            }
+        }
+
+        Ok(tree)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for FlatTree<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let flat = FlatNodes::<T>::deserialize(deserializer)?;
+
+        Tree::from_listed_nodes(flat.nodes.into_iter().map(|node| (node.id, node.parent, node.data)).collect())
+            .map(FlatTree)
+            .map_err(|err| serde::de::Error::custom(format!("invalid flat tree: {:?}", err)))
+    }
+}
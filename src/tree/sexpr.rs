@@ -0,0 +1,130 @@
+//! S-expression import and export. A node without children is written as its atom, and a node with children is written as a list
+//! that starts with its atom followed by its children, like `(root (child grandchild) leaf)`.
+
+use super::*;
+use std::fmt::Display;
+
+enum Token {
+    Open,
+    Close,
+    Atom(String),
+}
+
+//splits the text into parentheses and atoms, atoms with spaces or parentheses in them are quoted with backslash escapes.
+fn tokenize(text: &str) -> Result<Vec<Token>, TreeErr> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '"' => {
+                let mut atom = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => atom.push(chars.next().ok_or(InvalidNesting)?),
+                        Some(c) => atom.push(c),
+                        None => return Err(InvalidNesting),
+                    }
+                }
+                tokens.push(Token::Atom(atom));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut atom = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' || c == '"' { break }
+                    atom.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Atom(atom));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+//writes the atom, quoting it if it wouldn't be read back as a single atom.
+fn push_atom(sexpr: &mut String, atom: &str) {
+    if !atom.is_empty() && !atom.chars().any(|c| c.is_whitespace() || c == '(' || c == ')' || c == '"' || c == '\\') {
+        sexpr.push_str(atom);
+        return
+    }
+
+    sexpr.push('"');
+    for c in atom.chars() {
+        if c == '"' || c == '\\' { sexpr.push('\\') }
+        sexpr.push(c);
+    }
+    sexpr.push('"');
+}
+
+impl<T> Tree<T> {
+    fn to_sexpr_helper<D: Display, F: FnMut(&T) -> D>(&self, id: usize, sexpr: &mut String, render: &mut F) {
+        self.walk_sub_tree(id, |node_id, visit| {
+            let has_children = self.nodes[node_id].first_child.get().is_some();
+
+            if visit == Visit::Leave {
+                if has_children { sexpr.push(')') }
+                return
+            }
+
+            if node_id != id { sexpr.push(' ') }
+            if has_children { sexpr.push('(') }
+            push_atom(sexpr, &render(self.data_of(node_id).unwrap()).to_string());
+        });
+    }
+
+    /// Writes the tree as an s-expression, with the atom of each node made by render. An empty tree writes an empty string.
+    pub fn to_sexpr<D: Display, F: FnMut(&T) -> D>(&self, mut render: F) -> String {
+        let mut sexpr = String::new();
+
+        if let Some(root) = self.root {
+            self.to_sexpr_helper(root, &mut sexpr, &mut render);
+        }
+
+        sexpr
+    }
+
+    /// Creates a tree from an s-expression, with the data of each node made from its atom by parse_atom.
+    /// Fails with InvalidNesting if the parentheses or quotes don't match or a list doesn't start with an atom, and MultipleRoots if there is more than one top level expression.
+    pub fn from_sexpr<F: FnMut(&str) -> T>(text: &str, mut parse_atom: F) -> Result<Tree<T>, TreeErr> {
+        let mut tree = Tree::new();
+        let mut open: Vec<usize> = Vec::new();
+        let mut tokens = tokenize(text)?.into_iter();
+
+        while let Some(token) = tokens.next() {
+            let (atom, is_list) = match token {
+                Token::Open => match tokens.next() {
+                    Some(Token::Atom(atom)) => (atom, true),
+                    _ => return Err(InvalidNesting),
+                },
+                Token::Close => {
+                    open.pop().ok_or(InvalidNesting)?;
+                    continue
+                }
+                Token::Atom(atom) => (atom, false),
+            };
+
+            let data = parse_atom(&atom);
+            let id = match open.last() {
+                Some(&parent) => {
+                    let id = tree.get_node(data);
+                    tree.append_child(parent, id);
+                    id
+                }
+                None if tree.root.is_none() => tree.new_root(data),
+                None => return Err(MultipleRoots),
+            };
+
+            if is_list { open.push(id) }
+        }
+
+        if !open.is_empty() { return Err(InvalidNesting) }
+
+        Ok(tree)
+    }
+}
@@ -0,0 +1,33 @@
+//! Reading and writing the byte format straight from io::BufRead and io::Write, with buffering.
+
+use super::*;
+use std::io::{self, BufRead, Write};
+
+//the number of bytes collected before each write.
+const BUFFER_SIZE: usize = 8192;
+
+impl<'a, A: IntoBytes<'a>> Tree<A> {
+    /// Writes the same bytes as into_bytes to the writer, a buffer at a time.
+    pub fn write_to<W: Write>(&'a self, mut write: W) -> io::Result<()> {
+        let mut bytes = self.into_bytes();
+        let mut buffer = Vec::with_capacity(BUFFER_SIZE);
+
+        loop {
+            buffer.extend(bytes.by_ref().take(BUFFER_SIZE));
+            if buffer.is_empty() { break }
+
+            write.write_all(&buffer)?;
+            buffer.clear();
+        }
+
+        write.flush()
+    }
+}
+
+impl<A: FromBytes> Tree<A> {
+    /// Reads a tree written by write_to or into_bytes from the buffered reader, taking no bytes past the end of the tree.
+    /// Pass the reader by &mut to read whatever comes after the tree from it, and wrap a reader that isn't buffered in a BufReader.
+    pub fn read_from<R: BufRead>(read: R) -> Result<Tree<A>, ByteErr> {
+        Tree::from_io_bytes(&mut read.bytes())
+    }
+}
@@ -0,0 +1,151 @@
+//! XML import and export, enabled by the xml feature. Each node is an element, and the children of the node are the child elements.
+
+use super::*;
+use quick_xml::events::{Event, BytesStart, BytesEnd, BytesText};
+use quick_xml::{Reader, Writer};
+
+/// The tag, attributes and text of an xml element, made from the data of a node when writing xml and turned into the data of a node when reading it.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct XmlElement {
+    pub tag: String,
+    pub attributes: Vec<(String, String)>,
+    /// All of the text directly inside of the element, with the whitespace around it trimmed.
+    pub text: String,
+}
+
+impl XmlElement {
+    /// Creates an element without attributes or text.
+    pub fn new<S: Into<String>>(tag: S) -> XmlElement {
+        XmlElement {
+            tag: tag.into(),
+            ..XmlElement::default()
+        }
+    }
+}
+
+/// The ways reading xml into a tree can fail.
+#[derive(Debug)]
+pub enum XmlErr {
+    /// The text is not valid xml.
+    Xml(quick_xml::Error),
+    /// The xml can't be made into a tree, MultipleRoots if there is more than one top level element and InvalidNesting if an element isn't closed.
+    Tree(TreeErr),
+}
+
+impl From<quick_xml::Error> for XmlErr {
+    fn from(err: quick_xml::Error) -> Self {
+        XmlErr::Xml(err)
+    }
+}
+
+impl From<TreeErr> for XmlErr {
+    fn from(err: TreeErr) -> Self {
+        XmlErr::Tree(err)
+    }
+}
+
+//reads the tag and attributes of a start or empty event.
+fn read_element(start: &BytesStart) -> Result<XmlElement, quick_xml::Error> {
+    let mut element = XmlElement::new(String::from_utf8_lossy(start.name().as_ref()));
+
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        element.attributes.push((String::from_utf8_lossy(attribute.key.as_ref()).into_owned(), attribute.unescape_value()?.into_owned()));
+    }
+
+    Ok(element)
+}
+
+impl<T> Tree<T> {
+    //the tags of the open elements are kept on a stack to close them once their children are written, None for elements written as empty.
+    fn to_xml_helper<F: FnMut(&T) -> XmlElement>(&self, id: usize, writer: &mut Writer<Vec<u8>>, render: &mut F) {
+        let mut open: Vec<Option<String>> = Vec::new();
+
+        self.walk_sub_tree(id, |node_id, visit| {
+            if visit == Visit::Leave {
+                if let Some(tag) = open.pop().unwrap() {
+                    writer.write_event(Event::End(BytesEnd::new(tag.as_str()))).unwrap();
+                }
+                return
+            }
+
+            let element = render(self.data_of(node_id).unwrap());
+            let start = BytesStart::new(element.tag.as_str())
+                .with_attributes(element.attributes.iter().map(|(key, value)| (key.as_str(), value.as_str())));
+
+            if element.text.is_empty() && self.nodes[node_id].first_child.get().is_none() {
+                writer.write_event(Event::Empty(start)).unwrap();
+                open.push(None);
+                return
+            }
+
+            writer.write_event(Event::Start(start)).unwrap();
+            if !element.text.is_empty() {
+                writer.write_event(Event::Text(BytesText::new(&element.text))).unwrap();
+            }
+            open.push(Some(element.tag));
+        });
+    }
+
+    /// Writes the tree as xml, with the element of each node made by render. The text of an element is written before its children.
+    /// An empty tree writes an empty string.
+    pub fn to_xml<F: FnMut(&T) -> XmlElement>(&self, mut render: F) -> String {
+        let mut writer = Writer::new(Vec::new());
+
+        if let Some(root) = self.root {
+            self.to_xml_helper(root, &mut writer, &mut render);
+        }
+
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    /// Reads a tree from xml, with the data of each node made from its element by make_node. make_node is called once an element is closed,
+    /// so children are made before their parents. Declarations, comments and processing instructions are skipped.
+    pub fn from_xml<F: FnMut(XmlElement) -> T>(xml: &str, mut make_node: F) -> Result<Tree<T>, XmlErr> {
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+
+        //the open elements along with their finished children.
+        let mut open: Vec<(XmlElement, Vec<NestedNode<T>>)> = Vec::new();
+        let mut root = None;
+
+        loop {
+            let closed = match reader.read_event()? {
+                Event::Start(start) => {
+                    open.push((read_element(&start)?, Vec::new()));
+                    None
+                }
+                Event::Empty(start) => Some((read_element(&start)?, Vec::new())),
+                Event::End(_) => open.pop(),
+                Event::Text(text) => {
+                    if let Some((element, _)) = open.last_mut() {
+                        element.text.push_str(&text.unescape()?);
+                    }
+                    None
+                }
+                Event::CData(data) => {
+                    if let Some((element, _)) = open.last_mut() {
+                        element.text.push_str(&String::from_utf8_lossy(&data));
+                    }
+                    None
+                }
+                Event::Eof => break,
+                _ => None,
+            };
+
+            if let Some((element, children)) = closed {
+                let node = NestedNode { data: make_node(element), children };
+
+                match open.last_mut() {
+                    Some((_, siblings)) => siblings.push(node),
+                    None if root.is_some() => return Err(MultipleRoots.into()),
+                    None => root = Some(node),
+                }
+            }
+        }
+
+        if !open.is_empty() { return Err(InvalidNesting.into()) }
+
+        Ok(root.map_or_else(Tree::new, Tree::from_nested))
+    }
+}